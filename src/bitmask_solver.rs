@@ -0,0 +1,244 @@
+/*
+    Bitmask solver : The fast core of the backtracking solver. For every row, column and subgrid it
+    keeps a bitmask of the numbers used in it, where bit 0 stands for the number 1, bit 1 for the
+    number 2 and so on. Filling in or emptying a field only changes three bitmasks, and the numbers
+    which can still go into a field are found with a few bit operations, so nothing has to be
+    rebuilt while searching. The search always continues with the field that has the fewest
+    options left. Bitmasks of 64 bits support sudokus of up to 64x64. The same bitmasks are used
+    when filling in random sudokus.
+ */
+
+use crate::grid::{BoxShape, Digit, Grid};
+
+
+/// The widest sudoku the bitmask solver can solve
+pub(crate) const MAX_SIZE: usize = 64;
+
+/// The numbers used in every row, column and subgrid of a sudoku, as bitmasks. Fields are
+/// numbered row by row, as row * size + column.
+pub(crate) struct UsedNumbers {
+    /// For every row, the numbers used in it
+    rows: Vec<u64>,
+    /// For every column, the numbers used in it
+    columns: Vec<u64>,
+    /// For every subgrid, the numbers used in it
+    subgrids: Vec<u64>,
+    /// For every field, the subgrid it is in
+    subgrid_of: Vec<usize>,
+    /// The bitmask with the bits of all numbers set
+    all_numbers: u64,
+}
+
+impl UsedNumbers {
+    /// Returns the bitmasks of an empty sudoku
+    ///
+    /// # Arguments
+    /// * `box_shape` - The shape of the subgrids of the sudoku, at most `MAX_SIZE` wide
+    pub(crate) fn new(box_shape: BoxShape) -> UsedNumbers {
+        let size = box_shape.size();
+        UsedNumbers {
+            rows: vec![0; size],
+            columns: vec![0; size],
+            subgrids: vec![0; size],
+            subgrid_of: (0..size * size).map(|field| box_shape.subgrid_of(field / size, field % size)).collect(),
+            all_numbers: all_numbers(size),
+        }
+    }
+
+    /// Returns the numbers which can still go into a field, as a bitmask
+    ///
+    /// # Arguments
+    /// * `field` - The field, as row * size + column
+    pub(crate) fn options(&self, field: usize) -> u64 {
+        let size = self.rows.len();
+        let used = self.rows[field / size] | self.columns[field % size] | self.subgrids[self.subgrid_of[field]];
+        self.all_numbers & !used
+    }
+
+    /// Marks a number as used in the row, column and subgrid of a field
+    ///
+    /// # Arguments
+    /// * `field` - The field, as row * size + column
+    /// * `number` - The number filled in
+    pub(crate) fn insert(&mut self, field: usize, number: Digit) {
+        let size = self.rows.len();
+        self.rows[field / size] |= bit(number);
+        self.columns[field % size] |= bit(number);
+        self.subgrids[self.subgrid_of[field]] |= bit(number);
+    }
+
+    /// Marks a number as unused in the row, column and subgrid of a field
+    ///
+    /// # Arguments
+    /// * `field` - The field, as row * size + column
+    /// * `number` - The number taken out
+    pub(crate) fn remove(&mut self, field: usize, number: Digit) {
+        let size = self.rows.len();
+        self.rows[field / size] &= !bit(number);
+        self.columns[field % size] &= !bit(number);
+        self.subgrids[self.subgrid_of[field]] &= !bit(number);
+    }
+}
+
+/// A backtracking search through all solutions of a sudoku, which can stop after every solution
+/// and pick up where it left off
+pub(crate) struct BitmaskSearch {
+    /// The shape of the subgrids of the sudoku we are solving
+    box_shape: BoxShape,
+    /// The number in each field, row by row, 0 for empty
+    values: Vec<Digit>,
+    /// The numbers used in every row, column and subgrid
+    used: UsedNumbers,
+    /// The empty fields of the sudoku. The first ones are the fields we have guessed, in the order
+    /// of the stack.
+    empty: Vec<usize>,
+    /// For every guessed field, the numbers we still have to try
+    stack: Vec<u64>,
+    /// Whether the first field has been picked yet
+    started: bool,
+}
+
+impl BitmaskSearch {
+    /// Returns the search for all solutions of a sudoku
+    ///
+    /// # Arguments
+    /// * `sudoku` - A reference to the Sudoku grid.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the sudoku is wider than `MAX_SIZE`.
+    pub(crate) fn new(sudoku: &Grid) -> BitmaskSearch {
+        let size = sudoku.size();
+        assert!(size <= MAX_SIZE, "the bitmask solver supports sudokus of up to {}x{}", MAX_SIZE, MAX_SIZE);
+
+        let box_shape = sudoku.box_shape();
+        let mut search = BitmaskSearch {
+            box_shape,
+            values: vec![0; size * size],
+            used: UsedNumbers::new(box_shape),
+            empty: vec![],
+            stack: vec![],
+            started: false,
+        };
+
+        for (row, column, value) in sudoku.fields() {
+            let field = row * size + column;
+            match value {
+                None => search.empty.push(field),
+                Some(number) => {
+                    // a given that clashes with an earlier one means no solutions, which is
+                    // handled as in `solutions_with` in solver.rs
+                    if search.used.options(field) & bit(number) == 0 {
+                        search.started = true;
+                    }
+                    search.place(field, number);
+                }
+            }
+        }
+        search
+    }
+
+    /// Fills in a number, and marks it as used in its row, column and subgrid
+    ///
+    /// # Arguments
+    /// * `field` - The field, as row * size + column
+    /// * `number` - The number to fill in
+    fn place(&mut self, field: usize, number: Digit) {
+        self.values[field] = number;
+        self.used.insert(field, number);
+    }
+
+    /// Empties a field, and marks its number as unused in its row, column and subgrid
+    ///
+    /// # Arguments
+    /// * `field` - The field, as row * size + column
+    fn remove(&mut self, field: usize) {
+        self.used.remove(field, self.values[field]);
+        self.values[field] = 0;
+    }
+
+    /// Moves the empty field with the fewest options to the given position in the list of empty
+    /// fields, and returns its options
+    ///
+    /// # Arguments
+    /// * `position` - The position, which is also the number of fields guessed so far
+    fn pick_field(&mut self, position: usize) -> u64 {
+        let mut best = position;
+        let mut best_options = self.used.options(self.empty[position]);
+        for index in position + 1..self.empty.len() {
+            // stop early, for the reason given in `get_most_constrained_field` in solver.rs
+            if best_options.count_ones() <= 1 {
+                break;
+            }
+            let options = self.used.options(self.empty[index]);
+            if options.count_ones() < best_options.count_ones() {
+                best = index;
+                best_options = options;
+            }
+        }
+        self.empty.swap(position, best);
+        best_options
+    }
+
+    /// Returns the sudoku as far as it has been filled in
+    fn to_grid(&self) -> Grid {
+        let size = self.box_shape.size();
+        let mut grid = Grid::empty(self.box_shape);
+        for (field, &number) in self.values.iter().enumerate() {
+            grid.set(field / size, field % size, Some(number).filter(|&number| number != 0));
+        }
+        grid
+    }
+
+    /// Searches for the next solution, or returns None when all solutions have been found. This
+    /// takes the same steps as `GridSearch` in solver.rs, where they are explained, only with the
+    /// options of a field as a bitmask: the lowest bit is tried next, and a field is done once
+    /// its bitmask is 0.
+    pub(crate) fn next_solution(&mut self) -> Option<Grid> {
+        if !self.started {
+            self.started = true;
+            if self.empty.is_empty() {
+                return Some(self.to_grid());
+            }
+            let options = self.pick_field(0);
+            self.stack.push(options);
+        }
+
+        loop {
+            let depth = self.stack.len().checked_sub(1)?;
+            let field = self.empty[depth];
+            if self.values[field] != 0 {
+                self.remove(field);
+            }
+
+            let options = self.stack[depth];
+            if options == 0 {
+                self.stack.pop();
+                continue;
+            }
+            self.stack[depth] = options & (options - 1);
+            self.place(field, options.trailing_zeros() as Digit + 1);
+
+            if depth + 1 == self.empty.len() {
+                return Some(self.to_grid());
+            }
+            let next_options = self.pick_field(depth + 1);
+            if next_options != 0 {
+                self.stack.push(next_options);
+            }
+        }
+    }
+}
+
+/// Returns the bitmask with the bits of the numbers 1 through size set
+///
+/// # Arguments
+/// * `size` - The width of the sudoku, at most `MAX_SIZE`
+pub(crate) fn all_numbers(size: usize) -> u64 {
+    if size == MAX_SIZE { u64::MAX } else { (1 << size) - 1 }
+}
+
+/// Returns the bitmask for a single number
+pub(crate) fn bit(number: Digit) -> u64 {
+    1 << (number - 1)
+}
@@ -0,0 +1,287 @@
+/*
+    Canonical : Tells sudokus that are really different apart from sudokus that are the same up to
+    the symmetries of sudoku (see transforms). Out of all the grids a sudoku can be transformed
+    into, the canonical form is the one that comes first when the fields are read row by row, with
+    0 for an empty field. Two sudokus are the same up to symmetry exactly when their canonical
+    forms are equal.
+
+    The canonical form is found row by row. For the first two rows, every order of the columns is
+    tried together with every pair of rows that can come first. From then on only the partial grids
+    which are the smallest so far are continued, one row at a time. The numbers are relabelled in
+    the order in which they are first read, which always gives the smallest labelling. The number
+    of column orders grows very quickly with the size, so this is fast for 9x9 and still works for
+    12x12, but takes far too long for 16x16 and larger.
+ */
+
+use std::cmp::Ordering;
+use std::collections::HashSet;
+
+use crate::generator::Puzzle;
+use crate::grid::{Digit, Grid};
+use crate::transforms::Transform;
+
+
+/// The widest sudoku whose canonical form can be found in reasonable time (see the top of this
+/// file)
+pub const MAX_CANONICAL_SIZE: usize = 12;
+
+/// The number of rows that are read for every order of the columns, before only the smallest
+/// partial grids are kept
+const FIRST_ROWS: usize = 2;
+
+/// Everything the rest of a partial form depends on: the turned grid, the order of the columns,
+/// the rows used so far (in any order) and the new numbers
+type FormKey = (usize, Vec<usize>, Vec<usize>, Vec<Digit>);
+
+/// The first rows of a possible canonical form: the order of the columns, the rows picked so
+/// far, and the new numbers of the numbers read so far
+#[derive(Clone)]
+struct PartialForm {
+    /// Which of the turned grids the rows and columns are taken from
+    view: usize,
+    /// For every column, the column of the turned grid it comes from
+    columns: Vec<usize>,
+    /// For every row picked so far, the row of the turned grid it comes from
+    rows: Vec<usize>,
+    /// For every number, its new number, or 0 if it hasn't been read yet
+    labels: Vec<Digit>,
+    /// The new number for the next number that is read
+    next_label: usize,
+}
+
+impl PartialForm {
+    /// Returns the next row of the form when it is taken from the given row of the grid, together
+    /// with the form that includes it
+    ///
+    /// # Arguments
+    /// * `grid` - The turned grid the form is taken from
+    /// * `row` - The row of the grid to take
+    fn read_row(&self, grid: &Grid, row: usize) -> (Vec<Digit>, PartialForm) {
+        let mut form = self.clone();
+        let values = self.columns.iter().map(|&column| match grid.get(row, column) {
+            None => 0,
+            Some(number) => {
+                if form.labels[number as usize] == 0 {
+                    form.labels[number as usize] = form.next_label as Digit;
+                    form.next_label += 1;
+                }
+                form.labels[number as usize]
+            }
+        }).collect();
+        form.rows.push(row);
+        (values, form)
+    }
+
+    /// Returns everything the rest of the form depends on. Forms which read the same so far and
+    /// have the same key will also read the same from here on.
+    fn key(&self) -> FormKey {
+        let mut used_rows = self.rows.clone();
+        used_rows.sort_unstable();
+        (self.view, self.columns.clone(), used_rows, self.labels.clone())
+    }
+}
+
+/// The partial forms that read the smallest so far, which all read the same
+struct Smallest {
+    values: Option<Vec<Digit>>,
+    forms: Vec<PartialForm>,
+    seen: HashSet<FormKey>,
+}
+
+impl Smallest {
+    fn new() -> Smallest {
+        Smallest { values: None, forms: vec![], seen: HashSet::new() }
+    }
+
+    /// Keeps the form if it reads at least as small as the smallest so far
+    ///
+    /// # Arguments
+    /// * `values` - What the form reads, since the last time the smallest forms were picked
+    /// * `form` - The form
+    fn offer(&mut self, values: Vec<Digit>, form: PartialForm) {
+        let ordering = self.values.as_ref().map_or(Ordering::Less, |smallest| values.cmp(smallest));
+        match ordering {
+            Ordering::Greater => {}
+            Ordering::Less => {
+                self.values = Some(values);
+                self.forms.clear();
+                self.seen.clear();
+                self.seen.insert(form.key());
+                self.forms.push(form);
+            }
+            Ordering::Equal => {
+                if self.seen.insert(form.key()) {
+                    self.forms.push(form);
+                }
+            }
+        }
+    }
+}
+
+/// Returns the rows (or columns) which can come next, when the given ones are already placed.
+/// A new band can start with any row of a band that hasn't been used yet, and otherwise the band
+/// has to be finished first.
+///
+/// # Arguments
+/// * `used` - The rows placed so far, in order
+/// * `block_size` - The number of rows in a band (or columns in a stack)
+/// * `size` - The width of the sudoku
+fn next_lines(used: &[usize], block_size: usize, size: usize) -> Vec<usize> {
+    if used.len().is_multiple_of(block_size) {
+        let used_blocks: Vec<usize> = used.iter().map(|line| line / block_size).collect();
+        (0..size).filter(|line| !used_blocks.contains(&(line / block_size))).collect()
+    } else {
+        let block = used[used.len() - 1] / block_size;
+        (block * block_size..(block + 1) * block_size).filter(|line| !used.contains(line)).collect()
+    }
+}
+
+/// Calls the action for every order of the columns (or rows) that keeps the stacks together
+///
+/// # Arguments
+/// * `size` - The width of the sudoku
+/// * `block_size` - The number of columns in a stack (or rows in a band)
+/// * `order` - The columns placed so far, start with an empty list
+/// * `action` - What to do with every order
+fn for_each_order(size: usize, block_size: usize, order: &mut Vec<usize>, action: &mut impl FnMut(&[usize])) {
+    if order.len() == size {
+        action(order);
+        return;
+    }
+    for line in next_lines(order, block_size, size) {
+        order.push(line);
+        for_each_order(size, block_size, order, action);
+        order.pop();
+    }
+}
+
+/// Reads the first rows of the form in every possible way, and offers the results
+///
+/// # Arguments
+/// * `grid` - The turned grid the form is taken from
+/// * `form` - The form so far
+/// * `values` - What the form reads so far
+/// * `rows_left` - The number of rows still to read
+/// * `smallest` - Where the forms are offered
+fn read_first_rows(grid: &Grid, form: &PartialForm, values: Vec<Digit>, rows_left: usize, smallest: &mut Smallest) {
+    if rows_left == 0 {
+        smallest.offer(values, form.clone());
+        return;
+    }
+    for row in next_lines(&form.rows, grid.box_shape().rows, grid.size()) {
+        let (row_values, extended) = form.read_row(grid, row);
+        let mut all_values = values.clone();
+        all_values.extend(row_values);
+        read_first_rows(grid, &extended, all_values, rows_left - 1, smallest);
+    }
+}
+
+/// Returns the canonical form of a sudoku, together with the transform that turns the sudoku into
+/// it. The canonical form is the smallest grid the sudoku can be transformed into, when the fields
+/// are read row by row and empty fields count as 0. Sudokus with rectangular subgrids are always
+/// turned so that their subgrids are wider than they are high. Sudokus wider than
+/// `MAX_CANONICAL_SIZE` take far too long.
+///
+/// # Arguments
+/// * `sudoku` - A reference to the Sudoku grid, filled in or not
+pub fn canonicalize(sudoku: &Grid) -> (Grid, Transform) {
+    let box_shape = sudoku.box_shape();
+    let size = sudoku.size();
+
+    // square subgrids are tried both ways, rectangular ones only the wide way
+    let turns = if box_shape.is_square() {
+        vec![Transform::identity(box_shape), Transform::transpose(box_shape)]
+    } else if box_shape.rows > box_shape.columns {
+        vec![Transform::transpose(box_shape)]
+    } else {
+        vec![Transform::identity(box_shape)]
+    };
+    let views: Vec<Grid> = turns.iter().map(|turn| turn.apply(sudoku)).collect();
+    let view_shape = views[0].box_shape();
+
+    let first_rows = FIRST_ROWS.min(size);
+    let mut smallest = Smallest::new();
+    for (view, grid) in views.iter().enumerate() {
+        for_each_order(size, view_shape.columns, &mut vec![], &mut |columns| {
+            let start = PartialForm { view, columns: columns.to_vec(), rows: vec![], labels: vec![0; size + 1], next_label: 1 };
+            read_first_rows(grid, &start, vec![], first_rows, &mut smallest);
+        });
+    }
+
+    for _ in first_rows..size {
+        let mut next = Smallest::new();
+        for form in &smallest.forms {
+            for row in next_lines(&form.rows, view_shape.rows, size) {
+                let (values, extended) = form.read_row(&views[form.view], row);
+                next.offer(values, extended);
+            }
+        }
+        smallest = next;
+    }
+
+    // numbers that don't appear in the sudoku get the new numbers that are left, in order
+    let form = &smallest.forms[0];
+    let mut next_label = form.next_label;
+    let digits = (1..=size).map(|number| {
+        if form.labels[number] == 0 {
+            next_label += 1;
+            (next_label - 1) as Digit
+        } else {
+            form.labels[number]
+        }
+    }).collect();
+
+    let order = Transform::from_orders(view_shape, form.rows.clone(), form.columns.clone(), digits);
+    let transform = turns[form.view].then(&order);
+    (transform.apply(sudoku), transform)
+}
+
+/// Returns the canonical form of a sudoku (see `canonicalize`)
+///
+/// # Arguments
+/// * `sudoku` - A reference to the Sudoku grid, filled in or not
+pub fn canonical_form(sudoku: &Grid) -> Grid {
+    canonicalize(sudoku).0
+}
+
+/// Returns the canonical form of a puzzle, together with the transform that turns the puzzle
+/// into it. The canonical form is that of the sudoku to solve, and the solution is transformed
+/// along with it.
+///
+/// # Arguments
+/// * `puzzle` - The puzzle
+pub fn canonicalize_puzzle(puzzle: &Puzzle) -> (Puzzle, Transform) {
+    let (_, transform) = canonicalize(&puzzle.sudoku);
+    (transform.apply_to_puzzle(puzzle), transform)
+}
+
+/// Returns a transform which turns the first sudoku into the second, or None if no transform
+/// can do that (the sudokus are really different)
+///
+/// # Arguments
+/// * `first` - The sudoku to transform
+/// * `second` - The sudoku it should become
+///
+/// # Example
+///
+/// ```
+/// use sudoku_creator::{are_equivalent, generate_full_sudoku, seeded_rng, Transform};
+///
+/// let mut rng = seeded_rng(7);
+/// let sudoku = generate_full_sudoku(9, 9, &mut rng);
+/// let shuffled = Transform::random(sudoku.box_shape(), &mut rng).apply(&sudoku);
+///
+/// let transform = are_equivalent(&sudoku, &shuffled).unwrap();
+/// assert_eq!(transform.apply(&sudoku), shuffled);
+/// ```
+pub fn are_equivalent(first: &Grid, second: &Grid) -> Option<Transform> {
+    if first.size() != second.size() {
+        return None;
+    }
+    let (first_form, first_transform) = canonicalize(first);
+    let (second_form, second_transform) = canonicalize(second);
+    if first_form != second_form {
+        return None;
+    }
+    Some(first_transform.then(&second_transform.inverse()))
+}
@@ -0,0 +1,185 @@
+/*
+    Daily : The puzzle of the day. A calendar date, a namespace (for example the name of an app)
+    and a difficulty are turned into a seed, and the generator is run from that seed. Everyone who
+    asks for the same date, namespace and difficulty gets the same sudoku, without having to ask a
+    server for it.
+
+    The seed is made with the FNV-1a hash, which is simple enough to be computed the same way in
+    other languages, and unlike the hashers of the standard library it is guaranteed to never
+    change. The generator only has a budget of attempts (not of time), so a slow device finds the
+    same sudoku as a fast one. Clients do need to use the same version of this library, as changes
+    to the generator give different sudokus for the same seed.
+ */
+
+use std::fmt;
+use std::str::FromStr;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use crate::generator::{generate_puzzle_with, GenerationBudget, GenerationFailure, Puzzle};
+use crate::grading::Difficulty;
+use crate::grid::BoxShape;
+
+
+/// The number of attempts the puzzle of the day gets, which is more than the default budget as
+/// there is no second chance
+const DAILY_ATTEMPTS: usize = 1000;
+
+/// A day in the (proleptic Gregorian) calendar
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct Date {
+    year: i32,
+    month: u32,
+    day: u32,
+}
+
+impl Date {
+    /// Returns the date, or None if the day doesn't exist (like the 30th of February)
+    ///
+    /// # Arguments
+    /// * `year` - The year, like 2024
+    /// * `month` - The month, from 1 for January to 12 for December
+    /// * `day` - The day of the month, starting at 1
+    pub fn new(year: i32, month: u32, day: u32) -> Option<Date> {
+        if !(1..=12).contains(&month) || day < 1 || day > days_in_month(year, month) {
+            return None;
+        }
+        Some(Date { year, month, day })
+    }
+
+    /// Returns the current date in UTC, according to the clock of this computer
+    pub fn today_utc() -> Date {
+        let seconds = SystemTime::now().duration_since(UNIX_EPOCH).map_or(0, |duration| duration.as_secs());
+        Date::from_days_since_epoch((seconds / 86_400) as i64)
+    }
+
+    /// Returns the date which is the given number of days after the 1st of January 1970
+    ///
+    /// # Arguments
+    /// * `days` - The number of days since the 1st of January 1970
+    pub fn from_days_since_epoch(days: i64) -> Date {
+        // count from the 1st of March of the year 0, so that leap days fall at the end of a year,
+        // and split the days into eras of 400 years (which always have the same number of days)
+        let days = days + 719_468;
+        let era = days.div_euclid(146_097);
+        let day_of_era = days.rem_euclid(146_097);
+        let year_of_era = (day_of_era - day_of_era / 1_460 + day_of_era / 36_524 - day_of_era / 146_096) / 365;
+        let day_of_year = day_of_era - (365 * year_of_era + year_of_era / 4 - year_of_era / 100);
+        let month_from_march = (5 * day_of_year + 2) / 153;
+        let day = (day_of_year - (153 * month_from_march + 2) / 5 + 1) as u32;
+        let month = if month_from_march < 10 { month_from_march + 3 } else { month_from_march - 9 } as u32;
+        let year = (year_of_era + era * 400 + i64::from(month <= 2)) as i32;
+
+        Date { year, month, day }
+    }
+
+    /// The year, like 2024
+    pub fn year(&self) -> i32 {
+        self.year
+    }
+
+    /// The month, from 1 for January to 12 for December
+    pub fn month(&self) -> u32 {
+        self.month
+    }
+
+    /// The day of the month, starting at 1
+    pub fn day(&self) -> u32 {
+        self.day
+    }
+}
+
+impl fmt::Display for Date {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{:04}-{:02}-{:02}", self.year, self.month, self.day)
+    }
+}
+
+/// Returned when a text isn't a valid date in the form YYYY-MM-DD
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct ParseDateError {
+    /// The text that couldn't be read
+    pub text: String,
+}
+
+impl fmt::Display for ParseDateError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{:?} is not a date in the form YYYY-MM-DD", self.text)
+    }
+}
+
+impl std::error::Error for ParseDateError {}
+
+impl FromStr for Date {
+    type Err = ParseDateError;
+
+    /// Reads a date in the form YYYY-MM-DD, like 2024-01-19
+    fn from_str(text: &str) -> Result<Date, ParseDateError> {
+        let error = || ParseDateError { text: text.to_string() };
+        let parts: Vec<&str> = text.trim().split('-').collect();
+        if parts.len() != 3 {
+            return Err(error());
+        }
+        let year = parts[0].parse().map_err(|_| error())?;
+        let month = parts[1].parse().map_err(|_| error())?;
+        let day = parts[2].parse().map_err(|_| error())?;
+        Date::new(year, month, day).ok_or_else(error)
+    }
+}
+
+/// Returns the number of days in a month
+///
+/// # Arguments
+/// * `year` - The year, to know whether February has a leap day
+/// * `month` - The month, from 1 to 12
+fn days_in_month(year: i32, month: u32) -> u32 {
+    let is_leap_year = year % 4 == 0 && (year % 100 != 0 || year % 400 == 0);
+    match month {
+        2 if is_leap_year => 29,
+        2 => 28,
+        4 | 6 | 9 | 11 => 30,
+        _ => 31,
+    }
+}
+
+/// Returns the 64 bit FNV-1a hash of some bytes
+///
+/// # Arguments
+/// * `bytes` - The bytes to hash
+fn fnv1a(bytes: &[u8]) -> u64 {
+    bytes.iter().fold(0xcbf2_9ce4_8422_2325, |hash, &byte| (hash ^ u64::from(byte)).wrapping_mul(0x0100_0000_01b3))
+}
+
+/// Returns the seed of the puzzle of the day. This is the FNV-1a hash of the text
+/// "namespace|YYYY-MM-DD|Difficulty", for example "my-app|2024-01-19|Hard".
+///
+/// # Arguments
+/// * `date` - The day of the puzzle
+/// * `namespace` - Keeps the puzzles of different apps (or different series) apart
+/// * `difficulty` - The difficulty of the puzzle
+///
+/// # Example
+///
+/// ```
+/// use sudoku_creator::{daily_seed, Date, Difficulty};
+///
+/// let date = Date::new(2024, 1, 19).unwrap();
+/// assert_eq!(daily_seed(date, "my-app", Difficulty::Hard), daily_seed(date, "my-app", Difficulty::Hard));
+/// assert_ne!(daily_seed(date, "my-app", Difficulty::Hard), daily_seed(date, "other-app", Difficulty::Hard));
+/// ```
+pub fn daily_seed(date: Date, namespace: &str, difficulty: Difficulty) -> u64 {
+    fnv1a(format!("{}|{}|{}", namespace, date, difficulty).as_bytes())
+}
+
+/// Generates the standard 9x9 puzzle of the day. Everyone asking for the same date, namespace and
+/// difficulty gets the same sudoku, no matter how fast their computer is. If no sudoku of the
+/// difficulty is found within the attempts, everyone gets the same failure, which holds the
+/// closest sudoku.
+///
+/// # Arguments
+/// * `date` - The day of the puzzle
+/// * `namespace` - Keeps the puzzles of different apps (or different series) apart
+/// * `difficulty` - The difficulty of the puzzle
+pub fn generate_daily_puzzle(date: Date, namespace: &str, difficulty: Difficulty) -> Result<Puzzle, GenerationFailure> {
+    let budget = GenerationBudget { max_attempts: DAILY_ATTEMPTS, max_duration: None };
+    generate_puzzle_with(difficulty, BoxShape::new(3, 3), budget, daily_seed(date, namespace, difficulty))
+}
@@ -0,0 +1,512 @@
+/*
+    Dlx : Solves sudokus as an exact cover problem, with Donald Knuth's Algorithm X and his dancing
+    links. Every way to put a number in a field is a row of a matrix, and every rule is a column,
+    like "row 3 holds a 7" or "field (2, 5) holds a number". A solution is a set of rows which
+    covers every column exactly once. The rules come in families (see `ConstraintFamily`), so rules
+    for variants, like the diagonals of an X-sudoku, can be added without touching the search.
+
+    The matrix is stored as a grid of nodes linked in four directions, where each node is an index
+    into a few vectors. Covering a column unlinks it and all rows that use it, and uncovering links
+    them back in the reverse order, which is very cheap. Algorithm X always continues with the
+    column that has the fewest rows left.
+ */
+
+use crate::grid::{BoxShape, Digit, Grid};
+
+
+/// The node every column header is linked to
+const ROOT: usize = 0;
+
+/// An exact cover problem: a matrix of rows and columns, where a solution is a set of rows which
+/// covers every primary column exactly once, and every secondary column at most once
+///
+/// # Example
+///
+/// ```
+/// use sudoku_creator::dlx::ExactCover;
+///
+/// let mut problem = ExactCover::new(3, 0);
+/// problem.add_row(&[0, 1]);
+/// problem.add_row(&[2]);
+/// problem.add_row(&[1, 2]);
+/// problem.add_row(&[0]);
+///
+/// let solutions: Vec<Vec<usize>> = problem.solutions().collect();
+/// assert_eq!(solutions, vec![vec![0, 1], vec![3, 2]]);
+/// ```
+#[derive(Clone, Debug)]
+pub struct ExactCover {
+    /// For every node, the node to its left
+    left: Vec<usize>,
+    /// For every node, the node to its right
+    right: Vec<usize>,
+    /// For every node, the node above it
+    up: Vec<usize>,
+    /// For every node, the node below it
+    down: Vec<usize>,
+    /// For every node, the header of its column (the headers are nodes 1 up to the number of columns)
+    column: Vec<usize>,
+    /// For every node, the row it belongs to (not used for the headers)
+    row: Vec<usize>,
+    /// For every column header, the number of rows left in the column
+    rows_left: Vec<usize>,
+    /// For every row, its first node
+    row_start: Vec<usize>,
+}
+
+impl ExactCover {
+    /// Returns an exact cover problem without rows. Columns are numbered from 0, with the primary
+    /// columns first and the secondary columns after them.
+    ///
+    /// # Arguments
+    /// * `primary` - The number of columns which must be covered exactly once
+    /// * `secondary` - The number of columns which may be covered at most once
+    pub fn new(primary: usize, secondary: usize) -> ExactCover {
+        let headers = primary + secondary + 1;
+        let mut problem = ExactCover {
+            left: (0..headers).map(|node| node.wrapping_sub(1)).collect(),
+            right: (0..headers).map(|node| node + 1).collect(),
+            up: (0..headers).collect(),
+            down: (0..headers).collect(),
+            column: (0..headers).collect(),
+            row: vec![0; headers],
+            rows_left: vec![0; headers],
+            row_start: vec![],
+        };
+
+        // the primary columns form a ring with the root, and the secondary columns are only
+        // linked to themselves, so they never have to be chosen
+        problem.left[ROOT] = primary;
+        problem.right[primary] = ROOT;
+        for header in primary + 1..headers {
+            problem.left[header] = header;
+            problem.right[header] = header;
+        }
+        problem
+    }
+
+    /// Adds a row, and returns its number. Rows are numbered from 0, in the order they are added.
+    ///
+    /// # Arguments
+    /// * `columns` - The columns the row covers
+    ///
+    /// # Panics
+    ///
+    /// Panics if the row covers no columns, or a column that doesn't exist.
+    pub fn add_row(&mut self, columns: &[usize]) -> usize {
+        assert!(!columns.is_empty(), "a row has to cover at least one column");
+        let row = self.row_start.len();
+        let first = self.left.len();
+        self.row_start.push(first);
+
+        for (index, &column) in columns.iter().enumerate() {
+            let header = column + 1;
+            assert!(header < self.rows_left.len(), "there is no column {}", column);
+            let node = first + index;
+
+            // link the node in at the bottom of its column
+            self.up.push(self.up[header]);
+            self.down.push(header);
+            self.down[self.up[header]] = node;
+            self.up[header] = node;
+
+            // and at the end of its row
+            self.left.push(if index == 0 { node + columns.len() - 1 } else { node - 1 });
+            self.right.push(if index == columns.len() - 1 { first } else { node + 1 });
+
+            self.column.push(header);
+            self.row.push(row);
+            self.rows_left[header] += 1;
+        }
+        row
+    }
+
+    /// Returns an iterator over all solutions, as the numbers of the rows that were picked
+    pub fn solutions(self) -> ExactCoverSolutions {
+        let forced_columns = vec![false; self.rows_left.len()];
+        ExactCoverSolutions { problem: self, picked: vec![], forced: vec![], forced_columns, started: false, finished: false }
+    }
+
+    /// Removes a column, and every row which covers it from the other columns
+    fn cover(&mut self, header: usize) {
+        self.right[self.left[header]] = self.right[header];
+        self.left[self.right[header]] = self.left[header];
+        let mut row_node = self.down[header];
+        while row_node != header {
+            let mut node = self.right[row_node];
+            while node != row_node {
+                self.down[self.up[node]] = self.down[node];
+                self.up[self.down[node]] = self.up[node];
+                self.rows_left[self.column[node]] -= 1;
+                node = self.right[node];
+            }
+            row_node = self.down[row_node];
+        }
+    }
+
+    /// Undoes `cover`, which has to happen in the reverse order of covering
+    fn uncover(&mut self, header: usize) {
+        let mut row_node = self.up[header];
+        while row_node != header {
+            let mut node = self.left[row_node];
+            while node != row_node {
+                self.rows_left[self.column[node]] += 1;
+                self.down[self.up[node]] = node;
+                self.up[self.down[node]] = node;
+                node = self.left[node];
+            }
+            row_node = self.up[row_node];
+        }
+        self.right[self.left[header]] = header;
+        self.left[self.right[header]] = header;
+    }
+
+    /// Covers the columns of a row, other than the column of the given node
+    fn pick(&mut self, row_node: usize) {
+        let mut node = self.right[row_node];
+        while node != row_node {
+            self.cover(self.column[node]);
+            node = self.right[node];
+        }
+    }
+
+    /// Undoes `pick`
+    fn unpick(&mut self, row_node: usize) {
+        let mut node = self.left[row_node];
+        while node != row_node {
+            self.uncover(self.column[node]);
+            node = self.left[node];
+        }
+    }
+
+    /// Returns the primary column with the fewest rows left, or None if all are covered
+    fn smallest_column(&self) -> Option<usize> {
+        let mut best = None;
+        let mut header = self.right[ROOT];
+        while header != ROOT {
+            if best.is_none_or(|best| self.rows_left[header] < self.rows_left[best]) {
+                best = Some(header);
+                // an empty column can't be beaten, so there is no need to look further
+                if self.rows_left[header] == 0 {
+                    break;
+                }
+            }
+            header = self.right[header];
+        }
+        best
+    }
+}
+
+/// An iterator over the solutions of an exact cover problem, which only searches for the next
+/// solution when it is asked for. Created with `ExactCover::solutions`.
+#[derive(Clone, Debug)]
+pub struct ExactCoverSolutions {
+    problem: ExactCover,
+    /// The first node of every row we have picked, in order
+    picked: Vec<usize>,
+    /// The rows which are part of every solution
+    forced: Vec<usize>,
+    /// For every column header, whether one of the forced rows covers it
+    forced_columns: Vec<bool>,
+    /// Whether the search has started
+    started: bool,
+    /// Whether every option has been tried
+    finished: bool,
+}
+
+impl ExactCoverSolutions {
+    /// Makes a row part of every solution, by covering its columns before the search starts.
+    /// Returns False (and leaves no solutions) if it shares a column with an earlier forced row.
+    ///
+    /// # Arguments
+    /// * `row` - The number of the row
+    ///
+    /// # Panics
+    ///
+    /// Panics if the search has already started.
+    pub fn force_row(&mut self, row: usize) -> bool {
+        assert!(!self.started, "rows can only be forced before the search starts");
+        if self.finished {
+            return false;
+        }
+
+        let problem = &mut self.problem;
+        let first = problem.row_start[row];
+        let mut columns = vec![problem.column[first]];
+        let mut node = problem.right[first];
+        while node != first {
+            columns.push(problem.column[node]);
+            node = problem.right[node];
+        }
+        if columns.iter().any(|&header| self.forced_columns[header]) {
+            self.finished = true;
+            return false;
+        }
+        for header in columns {
+            self.forced_columns[header] = true;
+        }
+
+        problem.cover(problem.column[first]);
+        problem.pick(first);
+        self.forced.push(row);
+        true
+    }
+
+    /// Returns the rows of the current solution
+    fn solution(&self) -> Vec<usize> {
+        self.forced.iter().copied().chain(self.picked.iter().map(|&node| self.problem.row[node])).collect()
+    }
+}
+
+impl Iterator for ExactCoverSolutions {
+    type Item = Vec<usize>;
+
+    fn next(&mut self) -> Option<Vec<usize>> {
+        if self.finished {
+            return None;
+        }
+
+        // we go deeper at the start, and after a solution we go on with the next row of the last
+        // column instead
+        let mut deeper = !self.started;
+        self.started = true;
+
+        loop {
+            if deeper {
+                let Some(header) = self.problem.smallest_column() else {
+                    return Some(self.solution());
+                };
+                if self.problem.rows_left[header] == 0 {
+                    deeper = false;
+                    continue;
+                }
+                self.problem.cover(header);
+                let row_node = self.problem.down[header];
+                self.problem.pick(row_node);
+                self.picked.push(row_node);
+            } else {
+                // when nothing is picked anymore, every option has been tried
+                let Some(row_node) = self.picked.pop() else {
+                    self.finished = true;
+                    return None;
+                };
+                self.problem.unpick(row_node);
+                let header = self.problem.column[row_node];
+                let next_node = self.problem.down[row_node];
+                if next_node == header {
+                    self.problem.uncover(header);
+                    continue;
+                }
+                self.problem.pick(next_node);
+                self.picked.push(next_node);
+                deeper = true;
+            }
+        }
+    }
+}
+
+/// A family of rules of a sudoku, where every rule is that something happens exactly once (or at
+/// most once). Each way to put a number in a field satisfies some of the rules of each family.
+///
+/// The standard rules are `SUDOKU_RULES`. Variants add more families, like `Diagonals`.
+pub trait ConstraintFamily {
+    /// The number of rules in this family, for a sudoku with subgrids of the given shape
+    ///
+    /// # Arguments
+    /// * `box_shape` - The shape of the subgrids of the sudoku
+    fn count(&self, box_shape: BoxShape) -> usize;
+
+    /// Returns the rules (numbered from 0 to `count - 1`) which are satisfied by putting the
+    /// number in the field
+    ///
+    /// # Arguments
+    /// * `box_shape` - The shape of the subgrids of the sudoku
+    /// * `row` - The row of the field
+    /// * `column` - The column of the field
+    /// * `number` - The number put in the field
+    fn satisfied_by(&self, box_shape: BoxShape, row: usize, column: usize, number: Digit) -> Vec<usize>;
+
+    /// Whether every rule has to be satisfied exactly once (True), or at most once (False)
+    fn is_exact(&self) -> bool {
+        true
+    }
+}
+
+/// Every field holds exactly one number
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Cells;
+
+impl ConstraintFamily for Cells {
+    fn count(&self, box_shape: BoxShape) -> usize {
+        box_shape.size() * box_shape.size()
+    }
+
+    fn satisfied_by(&self, box_shape: BoxShape, row: usize, column: usize, _number: Digit) -> Vec<usize> {
+        vec![row * box_shape.size() + column]
+    }
+}
+
+/// Every row holds every number exactly once
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct RowNumbers;
+
+impl ConstraintFamily for RowNumbers {
+    fn count(&self, box_shape: BoxShape) -> usize {
+        box_shape.size() * box_shape.size()
+    }
+
+    fn satisfied_by(&self, box_shape: BoxShape, row: usize, _column: usize, number: Digit) -> Vec<usize> {
+        vec![row * box_shape.size() + number as usize - 1]
+    }
+}
+
+/// Every column holds every number exactly once
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct ColumnNumbers;
+
+impl ConstraintFamily for ColumnNumbers {
+    fn count(&self, box_shape: BoxShape) -> usize {
+        box_shape.size() * box_shape.size()
+    }
+
+    fn satisfied_by(&self, box_shape: BoxShape, _row: usize, column: usize, number: Digit) -> Vec<usize> {
+        vec![column * box_shape.size() + number as usize - 1]
+    }
+}
+
+/// Every subgrid holds every number exactly once
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct SubgridNumbers;
+
+impl ConstraintFamily for SubgridNumbers {
+    fn count(&self, box_shape: BoxShape) -> usize {
+        box_shape.size() * box_shape.size()
+    }
+
+    fn satisfied_by(&self, box_shape: BoxShape, row: usize, column: usize, number: Digit) -> Vec<usize> {
+        vec![box_shape.subgrid_of(row, column) * box_shape.size() + number as usize - 1]
+    }
+}
+
+/// Both long diagonals hold every number exactly once, as in an X-sudoku
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Diagonals;
+
+impl ConstraintFamily for Diagonals {
+    fn count(&self, box_shape: BoxShape) -> usize {
+        2 * box_shape.size()
+    }
+
+    fn satisfied_by(&self, box_shape: BoxShape, row: usize, column: usize, number: Digit) -> Vec<usize> {
+        let size = box_shape.size();
+        let mut rules = vec![];
+        if row == column {
+            rules.push(number as usize - 1);
+        }
+        if row + column == size - 1 {
+            rules.push(size + number as usize - 1);
+        }
+        rules
+    }
+}
+
+/// The rules of an ordinary sudoku
+pub const SUDOKU_RULES: [&dyn ConstraintFamily; 4] = [&Cells, &RowNumbers, &ColumnNumbers, &SubgridNumbers];
+
+/// An iterator over all solutions of a sudoku under a set of rules, which only searches for the
+/// next solution when it is asked for. Created with `solutions_with_rules`.
+#[derive(Clone, Debug)]
+pub struct DlxSolutions {
+    sudoku: Grid,
+    /// For every row of the matrix, the field (row, column) and the number it puts there
+    placements: Vec<(usize, usize, Digit)>,
+    search: ExactCoverSolutions,
+}
+
+impl Iterator for DlxSolutions {
+    type Item = Grid;
+
+    fn next(&mut self) -> Option<Grid> {
+        let rows = self.search.next()?;
+        let mut solution = self.sudoku.clone();
+        for row in rows {
+            let (row, column, number) = self.placements[row];
+            solution.set(row, column, Some(number));
+        }
+        Some(solution)
+    }
+}
+
+/// Returns an iterator over all solutions of a sudoku, which have to follow all of the given
+/// rules. Use `SUDOKU_RULES` for an ordinary sudoku, and add families for variants. Fields that
+/// none of the rules cover are left as they are, so without any rules the only solution is the
+/// sudoku itself. A field never gets more than one number, also when the rules don't include
+/// `Cells`.
+///
+/// # Arguments
+/// * `sudoku` - A reference to the Sudoku grid.
+/// * `rules` - The families of rules the solutions have to follow
+///
+/// # Example
+///
+/// ```
+/// use sudoku_creator::dlx::{solutions_with_rules, Diagonals, SUDOKU_RULES};
+/// use sudoku_creator::{BoxShape, Grid};
+///
+/// let sudoku = Grid::empty(BoxShape::new(2, 2));
+/// let x_sudoku_rules = [SUDOKU_RULES.as_slice(), &[&Diagonals]].concat();
+///
+/// assert_eq!(solutions_with_rules(&sudoku, &SUDOKU_RULES).count(), 288);
+/// assert_eq!(solutions_with_rules(&sudoku, &x_sudoku_rules).count(), 48);
+/// ```
+pub fn solutions_with_rules(sudoku: &Grid, rules: &[&dyn ConstraintFamily]) -> DlxSolutions {
+    let box_shape = sudoku.box_shape();
+    let size = sudoku.size();
+
+    // every family gets its own range of columns, with the secondary columns after the primary.
+    // The last secondary columns are one for every field, so that two placements in the same
+    // field can't both be picked when no family says so.
+    let primary: usize = rules.iter().filter(|family| family.is_exact()).map(|family| family.count(box_shape)).sum();
+    let secondary: usize = rules.iter().filter(|family| !family.is_exact()).map(|family| family.count(box_shape)).sum();
+    let field_columns = primary + secondary;
+    let mut offsets = vec![];
+    let (mut next_primary, mut next_secondary) = (0, primary);
+    for family in rules {
+        let next = if family.is_exact() { &mut next_primary } else { &mut next_secondary };
+        offsets.push(*next);
+        *next += family.count(box_shape);
+    }
+
+    let mut problem = ExactCover::new(primary, secondary + size * size);
+    let mut placements = vec![];
+    let mut givens = vec![];
+    for (row, column, value) in sudoku.fields() {
+        for number in 1..=size as Digit {
+            if value.is_some_and(|given| given != number) {
+                continue;
+            }
+            let mut columns: Vec<usize> = rules.iter().zip(&offsets)
+                .flat_map(|(family, &offset)| family.satisfied_by(box_shape, row, column, number).into_iter().map(move |rule| offset + rule))
+                .collect();
+
+            // a placement none of the rules say anything about can't be part of a solution
+            if columns.is_empty() {
+                continue;
+            }
+            columns.push(field_columns + row * size + column);
+            let matrix_row = problem.add_row(&columns);
+            placements.push((row, column, number));
+            if value.is_some() {
+                givens.push(matrix_row);
+            }
+        }
+    }
+
+    let mut search = problem.solutions();
+    for given in givens {
+        if !search.force_row(given) {
+            break;
+        }
+    }
+    DlxSolutions { sudoku: sudoku.clone(), placements, search }
+}
@@ -0,0 +1,1152 @@
+/*
+    Generator : Creates filled in sudokus, and sudokus to solve from them by removing numbers.
+    Puzzles can be generated to a target difficulty, or so that they need a chosen technique.
+ */
+
+use rand::seq::SliceRandom;     // random slices
+use rand::Rng;                  // random numbers
+use std::time::{Duration, Instant};
+
+use crate::bitmask_solver::{bit, UsedNumbers};
+use crate::grading::{grade_sudoku, technique_rating, Difficulty, Grade};
+use crate::grid::{BoxShape, Digit, Grid};
+use crate::logical_solver::{check_logical_size, solve_logically_with, LogicalSolveError, Technique};
+use crate::random::{random_seed, seeded_rng};
+use crate::solver::{count_solutions, has_unique_solution, solve_sudoku};
+use crate::transforms::{Reflection, Transform};
+
+
+/// Fill in a row with values, to create a filled in sudoku
+///
+/// # Arguments
+/// * `sudoku` - The Sudoku grid.
+/// * `numbers` - the numbers to fill in
+/// * `row_index` - The row to fill in
+/// * `column_offset` - the offset to start filling in values. So if column_offset = 3, we start filling in the values at column_idx = 3
+fn fill_row(mut sudoku : Grid, numbers:&[Digit], row_index:usize, column_offset:usize) -> Grid{
+
+    // values that run past the right edge wrap around to the start of the row
+    let width = sudoku.size();
+    for (i, &n) in numbers.iter().enumerate(){
+        let idx = (i + column_offset) % width;
+        sudoku.set(row_index, idx, Some(n));
+    }
+
+    sudoku
+}
+
+/// The widest sudoku that `FillMethod::Random` fills in field by field. A 25x25 takes a few
+/// hundredths of a second, but from 36x36 on it can take minutes, so wider sudokus are filled in
+/// with `FillMethod::Pattern`.
+pub const MAX_RANDOM_FILL_SIZE: usize = 25;
+
+/// Generates and returns a sudoku which has been completely filled in, using the standard subgrid
+/// shape for its size (see `BoxShape::for_size`)
+///
+/// # Arguments
+/// * `width` - The total width of the sudoku we want to generate (for a standard sudoku  = 9)
+/// * `height` - The total height of the sudoku we want to generate (for a standard sudoku = 9)
+/// * `rng` - The random number generator to draw from, for example `seeded_rng(seed)`
+///
+/// # Panics
+///
+/// Panics if width and height are not the same, or if the width can't be split into subgrids
+/// (like 5 or 7), as no sudoku of that size exists.
+pub fn generate_full_sudoku(width : usize, height : usize, rng: &mut impl Rng) -> Grid{
+    assert_eq!(width, height, "a sudoku must be as wide as it is high");
+    let box_shape = BoxShape::for_size(width)
+        .unwrap_or_else(|| panic!("a sudoku of width {} can't be split into subgrids", width));
+
+    generate_full_sudoku_with_box_shape(box_shape, rng)
+}
+
+/// How `generate_full_sudoku_with_method` fills in a sudoku
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum FillMethod {
+    /// Fills in the fields one by one with random numbers, and takes a step back when a field has
+    /// no numbers left. This can give any valid sudoku. Sudokus wider than `MAX_RANDOM_FILL_SIZE`
+    /// are filled in with `Pattern` instead.
+    #[default]
+    Random,
+    /// Fills in every row with the same numbers, shifted by a fixed pattern, and then shuffles the
+    /// rows, columns, bands and stacks around. This is very fast, even for large sudokus, but
+    /// every sudoku it gives is a shuffled version of the same pattern.
+    Pattern,
+}
+
+/// Generates and returns a sudoku which has been completely filled in, with subgrids of the
+/// given shape, using the `FillMethod::Random` method
+///
+/// # Arguments
+/// * `box_shape` - The shape of the subgrids (for a standard sudoku = 3x3). The sudoku will be
+///   `box_shape.size()` wide and high
+/// * `rng` - The random number generator to draw from, for example `seeded_rng(seed)`
+pub fn generate_full_sudoku_with_box_shape(box_shape: BoxShape, rng: &mut impl Rng) -> Grid{
+    generate_full_sudoku_with_method(box_shape, FillMethod::Random, rng)
+}
+
+/// Generates and returns a sudoku which has been completely filled in, with subgrids of the
+/// given shape and in the given way
+///
+/// # Arguments
+/// * `box_shape` - The shape of the subgrids (for a standard sudoku = 3x3). The sudoku will be
+///   `box_shape.size()` wide and high
+/// * `method` - How the sudoku is filled in
+/// * `rng` - The random number generator to draw from, for example `seeded_rng(seed)`
+pub fn generate_full_sudoku_with_method(box_shape: BoxShape, method: FillMethod, rng: &mut impl Rng) -> Grid{
+    match method {
+        FillMethod::Random => generate_random_sudoku(box_shape, rng),
+        FillMethod::Pattern => generate_pattern_sudoku(box_shape, rng),
+    }
+}
+
+/// Fills in an empty sudoku field by field with random numbers. The field with the fewest
+/// options is filled in first, and when a field has no options left we take a step back and try
+/// the next number there.
+///
+/// Now and then the first numbers lead into a corner where it takes very many steps back to get
+/// out, which happens more often for larger sudokus. So after a number of steps back we start
+/// over with an empty sudoku, allowing twice as many steps back every time.
+///
+/// Sudokus wider than `MAX_RANDOM_FILL_SIZE` take far too many steps back, so they are filled in
+/// with `FillMethod::Pattern` instead.
+///
+/// # Arguments
+/// * `box_shape` - The shape of the subgrids of the sudoku
+/// * `rng` - The random number generator to draw from
+fn generate_random_sudoku(box_shape: BoxShape, rng: &mut impl Rng) -> Grid{
+    let size = box_shape.size();
+    if size > MAX_RANDOM_FILL_SIZE {
+        return generate_pattern_sudoku(box_shape, rng);
+    }
+    let mut sudoku = Grid::empty(box_shape);
+
+    // for every row, column and subgrid, the numbers used in it as a bitmask (bit 0 for the
+    // number 1), so the options of a field are found without looking at the whole row again
+    let mut used = UsedNumbers::new(box_shape);
+
+    // for every field we have filled in, its row, its column and the numbers we still have to try
+    let mut stack: Vec<(usize, usize, Vec<Digit>)> = vec![];
+    let mut steps_back = 0;
+    let mut max_steps_back = size;
+    loop {
+        if steps_back > max_steps_back {
+            sudoku = Grid::empty(box_shape);
+            used = UsedNumbers::new(box_shape);
+            stack.clear();
+            steps_back = 0;
+            max_steps_back *= 2;
+        }
+
+        // pick the empty field with the fewest options (the first one, row by row, when there is
+        // a tie), and try its numbers in random order
+        let mut most_constrained: Option<(usize, usize, u64)> = None;
+        for (row, column, value) in sudoku.fields() {
+            if value.is_some() {
+                continue;
+            }
+            let options = used.options(row * size + column);
+            if most_constrained.is_none_or(|(_, _, best)| options.count_ones() < best.count_ones()) {
+                most_constrained = Some((row, column, options));
+                if options.count_ones() == 0 {
+                    break;
+                }
+            }
+        }
+        match most_constrained {
+            // every field is filled in
+            None => return sudoku,
+            Some((row, column, options)) => {
+                let mut numbers: Vec<Digit> = (1..=size as Digit).filter(|&number| options & bit(number) != 0).collect();
+                numbers.shuffle(rng);
+                stack.push((row, column, numbers));
+            }
+        }
+
+        // fill in the next number of the last field, taking steps back while fields run out
+        loop {
+            let (row, column, numbers) = stack.last_mut().expect("an empty sudoku can always be filled in");
+            let (row, column) = (*row, *column);
+            if let Some(old) = sudoku.get(row, column) {
+                used.remove(row * size + column, old);
+            }
+            match numbers.pop() {
+                Some(number) => {
+                    sudoku.set(row, column, Some(number));
+                    used.insert(row * size + column, number);
+                    break;
+                }
+                None => {
+                    sudoku.set(row, column, None);
+                    stack.pop();
+                    steps_back += 1;
+                }
+            }
+        }
+    }
+}
+
+/// Fills in a sudoku with a shifted pattern of random numbers, and shuffles it around (see
+/// `FillMethod::Pattern`)
+///
+/// # Arguments
+/// * `box_shape` - The shape of the subgrids of the sudoku
+/// * `rng` - The random number generator to draw from
+fn generate_pattern_sudoku(box_shape: BoxShape, rng: &mut impl Rng) -> Grid{
+    // This will generate a sudoku which is completely filled in and valid
+    let size = box_shape.size();
+
+    // usize = unsigned integer
+    let mut sudoku = Grid::empty(box_shape);
+
+    // get the numbers one through size in random order
+    let mut numbers: Vec<Digit> = (1..=size as Digit).collect();
+    numbers.shuffle(rng);
+
+    // Fill in the sudoku. Every row is the same set of numbers, shifted by the width of a subgrid
+    // compared to the row above it, and by one extra place at the start of each row of subgrids.
+    // For a 9x9 sudoku, this gives the offsets 0, 3, 6, 1, 4, 7, 2, 5, 8
+    for row_index in 0..size{
+        let column_offset = (row_index % box_shape.rows) * box_shape.columns + row_index / box_shape.rows;
+        sudoku = fill_row(sudoku, &numbers, row_index, column_offset);
+    }
+
+    // shuffle the rows within the bands, the bands themselves, the columns within the stacks and
+    // the stacks themselves, and for square subgrids maybe transpose (which, together with the
+    // shuffling, gives all rotations and reflections)
+    sudoku = Transform::random(box_shape, rng).apply(&sudoku);
+
+    // return the grid
+    sudoku
+
+}
+
+/// How strict `generate_sudoku_to_solve` is when deciding whether a field may be made empty
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum RemovalMode {
+    /// A removal is kept as long as the sudoku can still be solved, possibly in several ways
+    Solvable,
+    /// A removal is kept only if the sudoku still has exactly one solution
+    Unique,
+    /// Like `Unique`, but fields are emptied until none of the given numbers can go, so that
+    /// every given number is needed. The number of fields to empty is ignored. With a symmetry,
+    /// it is the groups of fields the symmetry ties together that can't go.
+    Minimal,
+}
+
+/// The symmetry of the pattern of given numbers in a sudoku to solve, as newspapers like to
+/// have it. Fields are emptied in groups (orbits) which the symmetry maps onto each other, so the
+/// pattern keeps its symmetry.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash)]
+pub enum Symmetry {
+    /// Fields are emptied one at a time, anywhere
+    #[default]
+    None,
+    /// The pattern looks the same after turning the sudoku by 180 degrees
+    HalfTurn,
+    /// The pattern looks the same after turning the sudoku by 90 degrees
+    QuarterTurn,
+    /// The pattern looks the same after mirroring the sudoku in a line
+    Mirror(Reflection),
+    /// The pattern looks the same after every rotation and every reflection
+    Dihedral,
+}
+
+impl Symmetry {
+    /// Returns the fields (as (row, column)) which the symmetry maps the field onto, including
+    /// the field itself, sorted row by row. These fields have to be emptied together.
+    ///
+    /// # Arguments
+    /// * `size` - The width of the sudoku
+    /// * `row` - The row of the field
+    /// * `column` - The column of the field
+    pub fn orbit(&self, size: usize, row: usize, column: usize) -> Vec<(usize, usize)> {
+        // every symmetry is made of mirrors: a quarter turn is a mirror in the main diagonal
+        // followed by one in the vertical line, and a half turn is a mirror in the vertical line
+        // followed by one in the horizontal line
+        let maps: Vec<Vec<Reflection>> = match *self {
+            Symmetry::None => vec![],
+            Symmetry::HalfTurn => vec![vec![Reflection::LeftRight, Reflection::TopBottom]],
+            Symmetry::QuarterTurn => vec![vec![Reflection::MainDiagonal, Reflection::LeftRight]],
+            Symmetry::Mirror(reflection) => vec![vec![reflection]],
+            Symmetry::Dihedral => vec![vec![Reflection::MainDiagonal, Reflection::LeftRight], vec![Reflection::LeftRight]],
+        };
+
+        // keep applying the maps, until no new fields turn up
+        let mut orbit = vec![(row, column)];
+        let mut index = 0;
+        while index < orbit.len() {
+            for map in &maps {
+                let field = map.iter().fold(orbit[index], |field, &reflection| mirror_field(size, reflection, field));
+                if !orbit.contains(&field) {
+                    orbit.push(field);
+                }
+            }
+            index += 1;
+        }
+        orbit.sort_unstable();
+        orbit
+    }
+}
+
+/// Returns where a field ends up when the sudoku is mirrored
+///
+/// # Arguments
+/// * `size` - The width of the sudoku
+/// * `reflection` - The line to mirror in
+/// * `field` - The field, as (row, column)
+fn mirror_field(size: usize, reflection: Reflection, (row, column): (usize, usize)) -> (usize, usize) {
+    let last = size - 1;
+    match reflection {
+        Reflection::LeftRight => (row, last - column),
+        Reflection::TopBottom => (last - row, column),
+        Reflection::MainDiagonal => (column, row),
+        Reflection::AntiDiagonal => (last - column, last - row),
+    }
+}
+
+/// The smallest and largest number of given numbers allowed, both included
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct GivenRange {
+    pub min: usize,
+    pub max: usize,
+}
+
+impl GivenRange {
+    /// Any number of given numbers is fine
+    pub const ANY: GivenRange = GivenRange { min: 0, max: usize::MAX };
+
+    /// Returns the range from `min` up to and including `max`
+    ///
+    /// # Arguments
+    /// * `min` - The smallest number of given numbers allowed
+    /// * `max` - The largest number of given numbers allowed
+    pub fn new(min: usize, max: usize) -> GivenRange {
+        GivenRange { min, max }
+    }
+
+    /// Whether the number of given numbers is allowed
+    ///
+    /// # Arguments
+    /// * `count` - The number of given numbers
+    pub fn contains(&self, count: usize) -> bool {
+        self.min <= count && count <= self.max
+    }
+}
+
+impl Default for GivenRange {
+    fn default() -> GivenRange {
+        GivenRange::ANY
+    }
+}
+
+/// How the given numbers of a sudoku to solve have to be spread out, for example to make sure no
+/// subgrid is left empty. By default anything goes.
+///
+/// # Example
+///
+/// ```
+/// use sudoku_creator::{generate_full_sudoku, generate_sudoku_to_solve_with, seeded_rng, GivenLimits, GivenRange, RemovalOptions};
+///
+/// let mut rng = seeded_rng(5);
+/// let filled = generate_full_sudoku(9, 9, &mut rng);
+/// let limits = GivenLimits { per_row: GivenRange::new(1, 5), per_box: GivenRange::new(2, 5), ..GivenLimits::default() };
+/// let options = RemovalOptions { limits, ..RemovalOptions::default() };
+///
+/// let report = generate_sudoku_to_solve_with(&filled, 50, &options, &mut rng);
+/// assert!(limits.allows(&report.sudoku));
+/// ```
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct GivenLimits {
+    /// The number of given numbers in every row
+    pub per_row: GivenRange,
+    /// The number of given numbers in every column
+    pub per_column: GivenRange,
+    /// The number of given numbers in every subgrid
+    pub per_box: GivenRange,
+    /// The number of times every number is given
+    pub per_digit: GivenRange,
+    /// The number of given numbers in the whole sudoku
+    pub total: GivenRange,
+}
+
+impl GivenLimits {
+    /// Whether the given numbers of a sudoku are spread out within the limits
+    ///
+    /// # Arguments
+    /// * `sudoku` - A reference to the Sudoku grid.
+    pub fn allows(&self, sudoku: &Grid) -> bool {
+        let tally = GivenTally::new(sudoku);
+        !tally.exceeds_maximum(self) && self.ranges().iter().zip(tally.counts()).all(|(range, counts)| counts.iter().all(|&count| count >= range.min))
+    }
+
+    /// The ranges for the rows, columns, subgrids, numbers and the whole sudoku, in that order
+    fn ranges(&self) -> [GivenRange; 5] {
+        [self.per_row, self.per_column, self.per_box, self.per_digit, self.total]
+    }
+}
+
+/// Limits on how long `generate_sudoku_to_solve_with` keeps trying to empty fields, and how
+/// strict it is about it
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct RemovalOptions {
+    /// Whether the sudoku only has to stay solvable, or must keep exactly one solution
+    pub mode: RemovalMode,
+    /// The symmetry the pattern of given numbers must have
+    pub symmetry: Symmetry,
+    /// The maximum number of fields to try to empty, or None to try every field
+    pub max_attempts: Option<usize>,
+    /// The maximum time to spend, or None for no time limit
+    pub max_duration: Option<Duration>,
+    /// How the given numbers have to be spread out
+    pub limits: GivenLimits,
+}
+
+impl Default for RemovalOptions {
+    fn default() -> RemovalOptions {
+        RemovalOptions {
+            mode: RemovalMode::Unique,
+            symmetry: Symmetry::None,
+            max_attempts: None,
+            max_duration: None,
+            limits: GivenLimits::default(),
+        }
+    }
+}
+
+/// Why `generate_sudoku_to_solve_with` stopped emptying fields
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum RemovalEnd {
+    /// As many fields were emptied as asked for
+    TargetReached,
+    /// Every field was tried, and none of the remaining ones can be emptied (together with the
+    /// fields the symmetry ties them to)
+    NothingLeftToRemove,
+    /// The maximum number of attempts was used up
+    OutOfAttempts,
+    /// The maximum time was used up
+    OutOfTime,
+    /// Every field was tried, but there are still more given numbers than the limits allow
+    LimitsExceeded,
+}
+
+/// What `generate_sudoku_to_solve_with` did
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct RemovalReport {
+    /// The sudoku to solve
+    pub sudoku: Grid,
+    /// The number of fields that were emptied
+    pub removed: usize,
+    /// The number of times we tried to empty a field (or a group of fields, with a symmetry)
+    pub attempts: usize,
+    /// The time that was spent
+    pub elapsed: Duration,
+    /// Why we stopped
+    pub end: RemovalEnd,
+}
+
+impl RemovalReport {
+    /// Whether as many fields were emptied as asked for
+    pub fn target_reached(&self) -> bool {
+        self.end == RemovalEnd::TargetReached
+    }
+}
+
+/// Returns a a sudoku with empty spaces that we can solve, based on a filled in example. If fewer
+/// fields can be emptied than asked for, the sudoku with as many empty fields as we could manage
+/// is returned (use `generate_sudoku_to_solve_with` to find out how many that was).
+///  # Arguments
+///
+/// * `filled_sudoku` - A reference to the Sudoku grid. This is completely filled in
+/// * `num_to_delete` - How many fields we want to make empty in our new sudoku
+/// * `mode` - Whether the new sudoku only has to be solvable, or must have exactly one solution
+/// * `rng` - The random number generator to draw from, for example `seeded_rng(seed)`
+pub fn generate_sudoku_to_solve(filled_sudoku : &Grid, num_to_delete: i32, mode: RemovalMode, rng: &mut impl Rng) -> Grid{
+    let options = RemovalOptions { mode, ..RemovalOptions::default() };
+    generate_sudoku_to_solve_with(filled_sudoku, num_to_delete.max(0) as usize, &options, rng).sudoku
+}
+
+/// Returns a sudoku with empty spaces that we can solve, based on a filled in example, together
+/// with a report of how it went. Every filled in field is tried once, in random order, so this
+/// always stops: when enough fields are empty, when no field is left to try, or when the budget in
+/// the options runs out.
+///
+/// With a symmetry, the fields the symmetry ties together are tried together, and only emptied
+/// if the sudoku is still acceptable with all of them empty. A group of fields is skipped if it
+/// would empty more fields than asked for.
+///
+/// A field is never emptied if that would leave fewer given numbers than the limits in the options
+/// allow. While a row, column, subgrid or number has more given numbers than allowed, its fields
+/// are tried first, and fields keep being emptied even when enough fields are empty already.
+///
+/// # Arguments
+/// * `filled_sudoku` - A reference to the Sudoku grid. This is completely filled in
+/// * `num_to_delete` - How many fields we want to make empty in our new sudoku
+/// * `options` - How strict to be, and how much may be spent
+/// * `rng` - The random number generator to draw from, for example `seeded_rng(seed)`
+pub fn generate_sudoku_to_solve_with(filled_sudoku: &Grid, num_to_delete: usize, options: &RemovalOptions, rng: &mut impl Rng) -> RemovalReport{
+    remove_fields(filled_sudoku, num_to_delete, options, |_| true, rng)
+}
+
+/// Returns a minimal version of a sudoku with exactly one solution: given numbers are emptied,
+/// row by row, as long as the sudoku keeps exactly one solution, so that in the end every given
+/// number is needed. Returns None if the sudoku doesn't have exactly one solution to begin with.
+///
+/// # Arguments
+/// * `sudoku` - A reference to the Sudoku grid, from any source
+pub fn minimise(sudoku: &Grid) -> Option<Grid>{
+    if !has_unique_solution(sudoku){
+        return None;
+    }
+
+    // emptying a field can only add solutions, so a given number that is needed now is still
+    // needed after more fields are emptied, and one pass is enough
+    let mut minimal = sudoku.clone();
+    for (row, column, value) in sudoku.fields().filter(|(_, _, value)| value.is_some()){
+        minimal.set(row, column, None);
+        if !has_unique_solution(&minimal){
+            minimal.set(row, column, value);
+        }
+    }
+    Some(minimal)
+}
+
+/// A generated sudoku to solve, together with its solution and how hard it is
+#[derive(Clone, Debug, PartialEq)]
+pub struct Puzzle {
+    /// The sudoku to solve
+    pub sudoku: Grid,
+    /// The completely filled in sudoku it was made from
+    pub solution: Grid,
+    pub grade: Grade,
+    /// The seed the sudoku was generated from. Calling the same generator with the same arguments
+    /// and this seed gives the same sudoku again.
+    pub seed: u64,
+}
+
+/// Generates a new sudoku to solve (see `generate_sudoku_to_solve`) and grades it. Sudokus wider
+/// than `MAX_LOGICAL_SIZE` can't be graded, so for those an error is returned.
+///
+/// # Arguments
+/// * `box_shape` - The shape of the subgrids of the sudoku
+/// * `num_to_delete` - How many fields we want to make empty in our new sudoku
+/// * `mode` - Whether the new sudoku only has to be solvable, or must have exactly one solution
+/// * `seed` - The seed for the random numbers, for example from `random_seed()`
+pub fn generate_graded_sudoku(box_shape: BoxShape, num_to_delete: i32, mode: RemovalMode, seed: u64) -> Result<Puzzle, LogicalSolveError>{
+    check_logical_size(box_shape)?;
+    let mut rng = seeded_rng(seed);
+    let solution = generate_full_sudoku_with_box_shape(box_shape, &mut rng);
+    let sudoku = generate_sudoku_to_solve(&solution, num_to_delete, mode, &mut rng);
+    let grade = grade_sudoku(&sudoku)?;
+
+    Ok(Puzzle { sudoku, solution, grade, seed })
+}
+
+/// Grades a sudoku whose size has already been checked with `check_logical_size`
+///
+/// # Arguments
+/// * `sudoku` - A reference to the Sudoku grid.
+fn grade_checked(sudoku: &Grid) -> Grade{
+    grade_sudoku(sudoku).expect("the size of the sudoku was checked before generating it")
+}
+
+/// Limits on how long `generate_puzzle_with` keeps trying to hit a difficulty
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct GenerationBudget {
+    /// The maximum number of filled in sudokus to try
+    pub max_attempts: usize,
+    /// The maximum time to spend, or None to only limit the number of attempts
+    pub max_duration: Option<Duration>,
+}
+
+impl Default for GenerationBudget {
+    fn default() -> GenerationBudget {
+        GenerationBudget { max_attempts: 200, max_duration: Some(Duration::from_secs(60)) }
+    }
+}
+
+/// What a generated sudoku was supposed to be like
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum GenerationTarget {
+    /// A sudoku of this difficulty
+    Difficulty(Difficulty),
+    /// A sudoku that needs this technique
+    Technique(Technique),
+    /// A sudoku with exactly one solution and givens in exactly the fields of a mask, which has
+    /// this many fields
+    Mask { givens: usize },
+    /// A sudoku whose given numbers are spread out within the limits that were asked for
+    Limits,
+}
+
+impl std::fmt::Display for GenerationTarget {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            GenerationTarget::Difficulty(difficulty) => write!(f, "{} sudoku", difficulty),
+            GenerationTarget::Technique(technique) => write!(f, "sudoku needing {}", technique),
+            GenerationTarget::Mask { givens } => write!(f, "unique sudoku with the {} givens of the mask", givens),
+            GenerationTarget::Limits => write!(f, "sudoku with its givens within the limits"),
+        }
+    }
+}
+
+/// Returned by the generators when the budget ran out before a sudoku of the requested kind was
+/// found
+#[derive(Clone, Debug, PartialEq)]
+pub struct GenerationFailure {
+    /// What was asked for
+    pub target: GenerationTarget,
+    /// The number of filled in sudokus that were tried
+    pub attempts: usize,
+    /// The time that was spent
+    pub elapsed: Duration,
+    /// The generated sudoku which came closest to the target, if any
+    pub closest: Option<Box<Puzzle>>,
+    /// The seed the generator was started with
+    pub seed: u64,
+}
+
+impl std::fmt::Display for GenerationFailure {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "no {} found in {} attempts ({:.1?}, seed {})", self.target, self.attempts, self.elapsed, self.seed)?;
+        if let Some(closest) = &self.closest {
+            write!(f, ", the closest was {}", closest.grade)?;
+        }
+        Ok(())
+    }
+}
+
+impl std::error::Error for GenerationFailure {}
+
+/// Generates a standard 9x9 sudoku with exactly one solution and the requested difficulty, from
+/// a random seed. The seed is returned with the puzzle, so `generate_puzzle_with` can make the
+/// same sudoku again.
+///
+/// # Arguments
+/// * `difficulty` - How hard the sudoku should be
+pub fn generate_puzzle(difficulty: Difficulty) -> Result<Puzzle, GenerationFailure>{
+    generate_puzzle_with(difficulty, BoxShape::new(3, 3), GenerationBudget::default(), random_seed())
+}
+
+/// Generates a sudoku with exactly one solution and the requested difficulty. Every attempt
+/// starts from a new filled in sudoku, from which fields are emptied for as long as the sudoku
+/// keeps a unique solution and doesn't become harder than requested. If the sudoku ends up in the
+/// requested difficulty it is returned, else we start over, until the budget runs out.
+///
+/// The attempts all draw from one generator started from the seed, so the same seed gives the
+/// same sudoku, unless the time budget runs out before the attempt that found it. Sudokus wider
+/// than `MAX_LOGICAL_SIZE` can't be graded, so for those the failure is returned right away.
+///
+/// # Arguments
+/// * `difficulty` - How hard the sudoku should be
+/// * `box_shape` - The shape of the subgrids of the sudoku
+/// * `budget` - How many attempts, and how much time, may be spent
+/// * `seed` - The seed for the random numbers, for example from `random_seed()`
+pub fn generate_puzzle_with(difficulty: Difficulty, box_shape: BoxShape, budget: GenerationBudget, seed: u64) -> Result<Puzzle, GenerationFailure>{
+    let start = Instant::now();
+    if check_logical_size(box_shape).is_err(){
+        return Err(GenerationFailure { target: GenerationTarget::Difficulty(difficulty), attempts: 0, elapsed: start.elapsed(), closest: None, seed });
+    }
+    let mut rng = seeded_rng(seed);
+    let mut closest: Option<Box<Puzzle>> = None;
+    let mut attempts = 0;
+
+    while attempts < budget.max_attempts && budget.max_duration.is_none_or(|max| start.elapsed() < max) {
+        attempts += 1;
+
+        // empty fields for as long as the sudoku doesn't get harder than requested
+        let solution = generate_full_sudoku_with_box_shape(box_shape, &mut rng);
+        let sudoku = remove_fields_while(&solution, |sudoku| grade_checked(sudoku).difficulty <= difficulty, &mut rng);
+        let grade = grade_checked(&sudoku);
+        let puzzle = Puzzle { sudoku, solution, grade, seed };
+
+        if puzzle.grade.difficulty == difficulty{
+            return Ok(puzzle);
+        }
+
+        // remember the attempt that came closest, in case we never hit the difficulty
+        let distance = |puzzle: &Puzzle| (puzzle.grade.difficulty as i32 - difficulty as i32).abs();
+        if closest.as_ref().is_none_or(|best| distance(&puzzle) < distance(best)){
+            closest = Some(Box::new(puzzle));
+        }
+    }
+
+    Err(GenerationFailure { target: GenerationTarget::Difficulty(difficulty), attempts, elapsed: start.elapsed(), closest, seed })
+}
+
+/// Generates a sudoku with exactly one solution which needs the required technique: it can't be
+/// solved without it, using the techniques that are rated lower or on the same level (the same
+/// whole point of `technique_rating`), but it can once the required technique is added. So a
+/// sudoku which needs an X-Wing can't be solved with singles and pairs, nor with the other
+/// techniques rated 3.x. Forbidden techniques are never used, so for example forbidding the
+/// X-Wing when asking for a Swordfish makes sure the sudoku can't be solved with an X-Wing
+/// instead.
+///
+/// Like `generate_puzzle_with`, every attempt starts from a new filled in sudoku, from which
+/// fields are emptied as long as the allowed techniques can still solve it. The result is then
+/// checked with the logical solver, and we start over until the budget runs out. As with
+/// `generate_puzzle_with`, the same seed gives the same sudoku.
+///
+/// # Arguments
+/// * `required` - The technique the sudoku must need
+/// * `forbidden` - Techniques which may not be used to solve the sudoku
+/// * `box_shape` - The shape of the subgrids of the sudoku
+/// * `budget` - How many attempts, and how much time, may be spent
+/// * `seed` - The seed for the random numbers, for example from `random_seed()`
+pub fn generate_puzzle_requiring(required: Technique, forbidden: &[Technique], box_shape: BoxShape, budget: GenerationBudget, seed: u64) -> Result<Puzzle, GenerationFailure>{
+    let start = Instant::now();
+    let mut rng = seeded_rng(seed);
+    let target = GenerationTarget::Technique(required);
+
+    // the techniques the sudoku must not be solvable with: those which are not forbidden, and
+    // which are rated lower than the required one or on the same level. Techniques come before
+    // harder ones in `Technique::ALL`, but a technique that comes after the required one can
+    // still be about as easy, like the hidden pair after the X-Wing.
+    let level = technique_rating(required).floor();
+    let others: Vec<Technique> = Technique::ALL.iter().copied()
+        .filter(|technique| *technique != required && !forbidden.contains(technique))
+        .filter(|technique| technique_rating(*technique).floor() <= level)
+        .collect();
+    let mut allowed = others.clone();
+    allowed.push(required);
+
+    // a forbidden required technique can never be needed, and the logical solver can't handle
+    // sudokus that are too wide
+    if forbidden.contains(&required) || check_logical_size(box_shape).is_err(){
+        return Err(GenerationFailure { target, attempts: 0, elapsed: start.elapsed(), closest: None, seed });
+    }
+    let is_solved_with = |sudoku: &Grid, techniques: &[Technique]| solve_logically_with(sudoku, techniques).is_ok_and(|solution| solution.solved);
+
+    let mut closest: Option<Box<Puzzle>> = None;
+    let mut attempts = 0;
+    while attempts < budget.max_attempts && budget.max_duration.is_none_or(|max| start.elapsed() < max) {
+        attempts += 1;
+
+        // the first quarter of the fields can be emptied without the (slower) logical solver, then
+        // we empty fields for as long as the allowed techniques can solve the sudoku
+        let solution = generate_full_sudoku_with_box_shape(box_shape, &mut rng);
+        let num_to_delete = (box_shape.size() * box_shape.size() / 4) as i32;
+        let sudoku = generate_sudoku_to_solve(&solution, num_to_delete, RemovalMode::Unique, &mut rng);
+        let sudoku = remove_fields_while(&sudoku, |sudoku| is_solved_with(sudoku, &allowed), &mut rng);
+
+        let grade = grade_checked(&sudoku);
+        let puzzle = Puzzle { sudoku, solution, grade, seed };
+
+        // the allowed techniques must solve the sudoku, and the required technique must be needed
+        if is_solved_with(&puzzle.sudoku, &allowed) && !is_solved_with(&puzzle.sudoku, &others){
+            return Ok(puzzle);
+        }
+
+        // remember the attempt whose hardest technique came closest to the required one in
+        // rating, where an attempt the techniques couldn't solve has the score of an unsolved sudoku
+        let distance = |puzzle: &Puzzle| {
+            let rating = match puzzle.grade.hardest_technique {
+                _ if !puzzle.grade.solved_logically => puzzle.grade.score,
+                Some(technique) => technique_rating(technique),
+                None => 0.0,
+            };
+            (rating - technique_rating(required)).abs()
+        };
+        if closest.as_ref().is_none_or(|best| distance(&puzzle) < distance(best)){
+            closest = Some(Box::new(puzzle));
+        }
+    }
+
+    Err(GenerationFailure { target, attempts, elapsed: start.elapsed(), closest, seed })
+}
+
+/// Generates a sudoku whose given numbers are spread out within the limits of the removal
+/// options. A single pass of `generate_sudoku_to_solve_with` often can't get there, as it tries
+/// every field only once, in one order. So every attempt starts from a new filled in sudoku and
+/// empties fields in a new random order, until the result is within the limits or the budget
+/// runs out. The same seed gives the same sudoku. Sudokus wider than `MAX_LOGICAL_SIZE` can't be
+/// graded, so for those the failure is returned right away.
+/// When the budget runs out, the failure holds the attempt whose counts were the least far
+/// outside of the limits.
+///
+/// # Arguments
+/// * `num_to_delete` - How many fields we want to make empty in every attempt
+/// * `options` - The limits, and how strict to be when emptying fields. The budget of the options
+///   is for every single attempt.
+/// * `box_shape` - The shape of the subgrids of the sudoku
+/// * `budget` - How many attempts, and how much time, may be spent
+/// * `seed` - The seed for the random numbers, for example from `random_seed()`
+///
+/// # Example
+///
+/// ```
+/// use sudoku_creator::{generate_puzzle_with_limits, BoxShape, GenerationBudget, GivenLimits, GivenRange, RemovalOptions};
+///
+/// let limits = GivenLimits { per_box: GivenRange::new(2, 5), total: GivenRange::new(26, 30), ..GivenLimits::default() };
+/// let options = RemovalOptions { limits, ..RemovalOptions::default() };
+/// let puzzle = generate_puzzle_with_limits(81, &options, BoxShape::new(3, 3), GenerationBudget::default(), 4).unwrap();
+/// assert!(limits.allows(&puzzle.sudoku));
+/// ```
+pub fn generate_puzzle_with_limits(num_to_delete: usize, options: &RemovalOptions, box_shape: BoxShape, budget: GenerationBudget, seed: u64) -> Result<Puzzle, GenerationFailure>{
+    let start = Instant::now();
+    let target = GenerationTarget::Limits;
+    if check_logical_size(box_shape).is_err(){
+        return Err(GenerationFailure { target, attempts: 0, elapsed: start.elapsed(), closest: None, seed });
+    }
+
+    let mut rng = seeded_rng(seed);
+    let mut closest: Option<(usize, Box<Puzzle>)> = None;
+    let mut attempts = 0;
+    while attempts < budget.max_attempts && budget.max_duration.is_none_or(|max| start.elapsed() < max) {
+        attempts += 1;
+
+        let solution = generate_full_sudoku_with_box_shape(box_shape, &mut rng);
+        let sudoku = generate_sudoku_to_solve_with(&solution, num_to_delete, options, &mut rng).sudoku;
+        let distance = GivenTally::new(&sudoku).distance(&options.limits);
+        if distance == 0 {
+            let grade = grade_checked(&sudoku);
+            return Ok(Puzzle { sudoku, solution, grade, seed });
+        }
+        if closest.as_ref().is_none_or(|(least, _)| distance < *least){
+            let grade = grade_checked(&sudoku);
+            closest = Some((distance, Box::new(Puzzle { sudoku, solution, grade, seed })));
+        }
+    }
+
+    let closest = closest.map(|(_, puzzle)| puzzle);
+    Err(GenerationFailure { target, attempts, elapsed: start.elapsed(), closest, seed })
+}
+
+/// The number of transformed versions of every filled in sudoku that `generate_puzzle_from_mask`
+/// tries, besides the sudoku itself
+const TRANSFORMS_PER_SUDOKU: usize = 9;
+
+/// The number of solutions `generate_puzzle_from_mask` counts at most, to find the attempt that
+/// came closest to a unique solution
+const SOLUTIONS_TO_COUNT: usize = 16;
+
+/// Generates a sudoku whose givens are exactly the fields marked in a mask, for example to draw a
+/// shape with the givens. Every attempt takes a filled in sudoku (a new one from
+/// `generate_full_sudoku_with_box_shape`, or the last new one with its rows and columns moved
+/// around) and empties the fields outside the mask. Only the rows and columns are moved, since
+/// swapping the numbers around can't change whether the sudoku has one solution.
+/// The first sudoku with exactly one solution is returned, else we keep trying until the budget
+/// runs out. Every sudoku that is checked counts as an attempt, and the same seed gives the same
+/// sudoku.
+///
+/// Some masks can never give a unique sudoku: a mask with fewer than width - 1 givens, or with
+/// two empty rows in one band (or two empty bands, and likewise for columns), as those rows could
+/// be swapped in any solution. For those, and for sudokus wider than `MAX_LOGICAL_SIZE` which
+/// can't be graded, the failure is returned right away, after 0 attempts.
+/// When the budget runs out, the failure holds the attempt with the fewest solutions.
+///
+/// # Arguments
+/// * `mask` - For every row, for every column, True if the field should be a given
+/// * `box_shape` - The shape of the subgrids of the sudoku
+/// * `budget` - How many attempts, and how much time, may be spent
+/// * `seed` - The seed for the random numbers, for example from `random_seed()`
+///
+/// # Panics
+///
+/// Panics if the mask doesn't have as many rows and columns as the sudoku.
+///
+/// # Example
+///
+/// ```
+/// use sudoku_creator::{generate_puzzle_from_mask, BoxShape, GenerationBudget};
+///
+/// // everything except an empty cross through the middle
+/// let mask: Vec<Vec<bool>> = (0..9).map(|row| (0..9).map(|column| row != 4 && column != 4).collect()).collect();
+/// let puzzle = generate_puzzle_from_mask(&mask, BoxShape::new(3, 3), GenerationBudget::default(), 7).unwrap();
+/// assert_eq!(puzzle.sudoku.get(4, 4), None);
+/// assert_eq!(puzzle.sudoku.get(0, 0), puzzle.solution.get(0, 0));
+/// ```
+pub fn generate_puzzle_from_mask(mask: &[Vec<bool>], box_shape: BoxShape, budget: GenerationBudget, seed: u64) -> Result<Puzzle, GenerationFailure>{
+    let start = Instant::now();
+    let size = box_shape.size();
+    assert!(mask.len() == size && mask.iter().all(|row| row.len() == size), "the mask should be {}x{}", size, size);
+
+    let givens = mask.iter().flatten().filter(|&&given| given).count();
+    let target = GenerationTarget::Mask { givens };
+    if !can_be_unique(mask, box_shape) || check_logical_size(box_shape).is_err(){
+        return Err(GenerationFailure { target, attempts: 0, elapsed: start.elapsed(), closest: None, seed });
+    }
+
+    let mut rng = seeded_rng(seed);
+    let mut closest: Option<(usize, Box<Puzzle>)> = None;
+    let mut filled = generate_full_sudoku_with_box_shape(box_shape, &mut rng);
+    let mut attempts = 0;
+    while attempts < budget.max_attempts && budget.max_duration.is_none_or(|max| start.elapsed() < max) {
+        // start with a new filled in sudoku, and try some transforms of it before the next one
+        let solution = if attempts % (TRANSFORMS_PER_SUDOKU + 1) == 0 {
+            if attempts > 0 {
+                filled = generate_full_sudoku_with_box_shape(box_shape, &mut rng);
+            }
+            filled.clone()
+        } else {
+            Transform::random_moves(box_shape, &mut rng).apply(&filled)
+        };
+        attempts += 1;
+
+        let mut sudoku = solution.clone();
+        for (row, column, _) in solution.fields() {
+            if !mask[row][column] {
+                sudoku.set(row, column, None);
+            }
+        }
+
+        let count = count_solutions(&sudoku, SOLUTIONS_TO_COUNT);
+        if count == 1 {
+            let grade = grade_checked(&sudoku);
+            return Ok(Puzzle { sudoku, solution, grade, seed });
+        }
+        if closest.as_ref().is_none_or(|(fewest, _)| count < *fewest){
+            let grade = grade_checked(&sudoku);
+            closest = Some((count, Box::new(Puzzle { sudoku, solution, grade, seed })));
+        }
+    }
+
+    let closest = closest.map(|(_, puzzle)| puzzle);
+    Err(GenerationFailure { target, attempts, elapsed: start.elapsed(), closest, seed })
+}
+
+/// Returns False if no sudoku with givens in exactly the fields of the mask can have a unique
+/// solution, because there are too few givens or because rows (or columns) without givens could
+/// be swapped
+///
+/// # Arguments
+/// * `mask` - For every row, for every column, True if the field should be a given
+/// * `box_shape` - The shape of the subgrids of the sudoku
+fn can_be_unique(mask: &[Vec<bool>], box_shape: BoxShape) -> bool{
+    let size = box_shape.size();
+    let givens = mask.iter().flatten().filter(|&&given| given).count();
+    let empty_rows: Vec<bool> = (0..size).map(|row| (0..size).all(|column| !mask[row][column])).collect();
+    let empty_columns: Vec<bool> = (0..size).map(|column| (0..size).all(|row| !mask[row][column])).collect();
+
+    // two empty rows in one band, or two empty bands, can be swapped without touching a given
+    let can_swap = |empty: &[bool], block_size: usize| {
+        let empty_blocks = empty.chunks(block_size).filter(|block| block.iter().all(|&line| line)).count();
+        empty_blocks >= 2 || empty.chunks(block_size).any(|block| block.iter().filter(|&&line| line).count() >= 2)
+    };
+    // with two numbers that are never given, those two numbers could be swapped in any solution
+    givens + 1 >= size && !can_swap(&empty_rows, box_shape.rows) && !can_swap(&empty_columns, box_shape.columns)
+}
+
+/// Empties as many fields of a sudoku as possible, in random order, while keeping exactly one
+/// solution and as long as the sudoku passes the given check
+///
+/// # Arguments
+/// * `filled_sudoku` - A reference to the Sudoku grid to start from
+/// * `keep` - Returns True if the sudoku, with one more field emptied, is still acceptable
+/// * `rng` - The random number generator to draw from
+fn remove_fields_while(filled_sudoku: &Grid, keep: impl Fn(&Grid) -> bool, rng: &mut impl Rng) -> Grid{
+    remove_fields(filled_sudoku, usize::MAX, &RemovalOptions::default(), keep, rng).sudoku
+}
+
+/// Empties fields of a sudoku, trying every filled in field once in random order (together with
+/// the fields the symmetry ties it to), until enough fields are empty or the budget runs out.
+/// Fields stay empty if the sudoku can still be solved (in exactly one way, if asked for) and
+/// passes the given check.
+///
+/// # Arguments
+/// * `filled_sudoku` - A reference to the Sudoku grid to start from
+/// * `num_to_delete` - How many fields we want to make empty
+/// * `options` - How strict to be, and how much may be spent
+/// * `keep` - Returns True if the sudoku, with one more field emptied, is still acceptable
+/// * `rng` - The random number generator to draw from
+fn remove_fields(filled_sudoku: &Grid, num_to_delete: usize, options: &RemovalOptions, keep: impl Fn(&Grid) -> bool, rng: &mut impl Rng) -> RemovalReport{
+    let start = Instant::now();
+    let mut sudoku_to_solve: Grid = filled_sudoku.clone();
+    let size = sudoku_to_solve.size();
+
+    // a minimal sudoku has no target, we just keep going until nothing can be emptied
+    let num_to_delete = if options.mode == RemovalMode::Minimal { usize::MAX } else { num_to_delete };
+
+    // group the filled in fields which the symmetry ties together, and visit every group once,
+    // in random order
+    let mut grouped = vec![false; size * size];
+    let mut orbits: Vec<Vec<(usize, usize)>> = vec![];
+    for (row, column, value) in sudoku_to_solve.fields(){
+        if value.is_none() || grouped[row * size + column]{
+            continue;
+        }
+        let orbit: Vec<(usize, usize)> = options.symmetry.orbit(size, row, column).into_iter()
+            .filter(|&(row, column)| sudoku_to_solve.get(row, column).is_some())
+            .collect();
+        for &(row, column) in &orbit{
+            grouped[row * size + column] = true;
+        }
+        orbits.push(orbit);
+    }
+    orbits.shuffle(rng);
+
+    let limits = &options.limits;
+    let mut tally = GivenTally::new(&sudoku_to_solve);
+    let mut tried = vec![false; orbits.len()];
+    let mut removed = 0;
+    let mut attempts = 0;
+    let mut end = RemovalEnd::NothingLeftToRemove;
+    loop {
+        // while there are too many given numbers somewhere, the fields there go first
+        let exceeded = tally.exceeds_maximum(limits);
+        let reduces_excess = |index: usize| exceeded && tally.reduces_excess(limits, &sudoku_to_solve, &orbits[index]);
+        let next = (0..orbits.len()).filter(|&index| !tried[index]).find(|&index| !exceeded || reduces_excess(index))
+            .or_else(|| tried.iter().position(|&done| !done));
+        let Some(index) = next else { break };
+        tried[index] = true;
+        let orbit = &orbits[index];
+
+        // too many given numbers count for more than the number of fields asked for
+        let may_overshoot = reduces_excess(index);
+        if removed >= num_to_delete && !exceeded{
+            break;
+        }
+        if (removed + orbit.len() > num_to_delete && !may_overshoot) || tally.breaks_minimum(limits, &sudoku_to_solve, orbit){
+            continue;
+        }
+        if options.max_attempts.is_some_and(|max| attempts >= max){
+            end = RemovalEnd::OutOfAttempts;
+            break;
+        }
+        if options.max_duration.is_some_and(|max| start.elapsed() >= max){
+            end = RemovalEnd::OutOfTime;
+            break;
+        }
+        attempts += 1;
+
+        // we try to solve, if we can (in exactly one way, if asked for), we will leave it removed
+        let old_vals: Vec<Option<Digit>> = orbit.iter().map(|&(row, column)| sudoku_to_solve.get(row, column)).collect();
+        tally.count(&sudoku_to_solve, orbit, false);
+        for &(row, column) in orbit{
+            sudoku_to_solve.set(row, column, None);
+        }
+        let solved = match options.mode {
+            RemovalMode::Solvable => solve_sudoku(&sudoku_to_solve),
+            RemovalMode::Unique | RemovalMode::Minimal => has_unique_solution(&sudoku_to_solve),
+        };
+        if solved && keep(&sudoku_to_solve){
+            removed += orbit.len();
+        }
+        else {
+            //reset and try the next group of fields.
+            for (&(row, column), &old_val) in orbit.iter().zip(&old_vals){
+                sudoku_to_solve.set(row, column, old_val);
+            }
+            tally.count(&sudoku_to_solve, orbit, true);
+        }
+    }
+    if removed >= num_to_delete || (options.mode == RemovalMode::Minimal && end == RemovalEnd::NothingLeftToRemove){
+        end = RemovalEnd::TargetReached;
+    }
+    if tally.exceeds_maximum(limits) && matches!(end, RemovalEnd::TargetReached | RemovalEnd::NothingLeftToRemove){
+        end = RemovalEnd::LimitsExceeded;
+    }
+
+    RemovalReport { sudoku: sudoku_to_solve, removed, attempts, elapsed: start.elapsed(), end }
+}
+
+/// The number of given numbers in every row, column, subgrid, of every number and in total, kept
+/// up to date while fields are emptied
+struct GivenTally {
+    rows: Vec<usize>,
+    columns: Vec<usize>,
+    boxes: Vec<usize>,
+    /// For every number (minus one), how often it is given
+    digits: Vec<usize>,
+    total: usize,
+}
+
+impl GivenTally {
+    /// Counts the given numbers of a sudoku
+    ///
+    /// # Arguments
+    /// * `sudoku` - A reference to the Sudoku grid.
+    fn new(sudoku: &Grid) -> GivenTally {
+        let size = sudoku.size();
+        let mut tally = GivenTally { rows: vec![0; size], columns: vec![0; size], boxes: vec![0; size], digits: vec![0; size], total: 0 };
+        let filled: Vec<(usize, usize)> = sudoku.fields().filter(|(_, _, value)| value.is_some()).map(|(row, column, _)| (row, column)).collect();
+        tally.count(sudoku, &filled, true);
+        tally
+    }
+
+    /// Adds the given numbers in some fields to the counts, or takes them off
+    ///
+    /// # Arguments
+    /// * `sudoku` - A reference to the Sudoku grid, with the fields still filled in
+    /// * `fields` - The fields, as (row, column)
+    /// * `add` - True to add the numbers, False to take them off
+    fn count(&mut self, sudoku: &Grid, fields: &[(usize, usize)], add: bool) {
+        for &(row, column) in fields {
+            let Some(number) = sudoku.get(row, column) else { continue };
+            let subgrid = sudoku.box_shape().subgrid_of(row, column);
+            for count in [&mut self.rows[row], &mut self.columns[column], &mut self.boxes[subgrid], &mut self.digits[number as usize - 1], &mut self.total] {
+                if add { *count += 1 } else { *count -= 1 }
+            }
+        }
+    }
+
+    /// The counts for the rows, columns, subgrids, numbers and the whole sudoku, in the order of
+    /// `GivenLimits::ranges`
+    fn counts(&self) -> [&[usize]; 5] {
+        [&self.rows, &self.columns, &self.boxes, &self.digits, std::slice::from_ref(&self.total)]
+    }
+
+    /// Returns the counts after the fields have been emptied
+    ///
+    /// # Arguments
+    /// * `sudoku` - A reference to the Sudoku grid, with the fields still filled in
+    /// * `fields` - The fields to empty, as (row, column)
+    fn without(&self, sudoku: &Grid, fields: &[(usize, usize)]) -> GivenTally {
+        let mut tally = GivenTally {
+            rows: self.rows.clone(),
+            columns: self.columns.clone(),
+            boxes: self.boxes.clone(),
+            digits: self.digits.clone(),
+            total: self.total,
+        };
+        tally.count(sudoku, fields, false);
+        tally
+    }
+
+    /// How far the counts are outside of the limits: for every row, column, subgrid and number,
+    /// and for the whole sudoku, how many given numbers there are too many or too few, added up
+    ///
+    /// # Arguments
+    /// * `limits` - The limits
+    fn distance(&self, limits: &GivenLimits) -> usize {
+        limits.ranges().iter().zip(self.counts())
+            .map(|(range, counts)| counts.iter().map(|&count| range.min.saturating_sub(count) + count.saturating_sub(range.max)).sum::<usize>())
+            .sum()
+    }
+
+    /// Whether somewhere there are more given numbers than the limits allow
+    ///
+    /// # Arguments
+    /// * `limits` - The limits
+    fn exceeds_maximum(&self, limits: &GivenLimits) -> bool {
+        limits.ranges().iter().zip(self.counts()).any(|(range, counts)| counts.iter().any(|&count| count > range.max))
+    }
+
+    /// Whether emptying the fields would leave fewer given numbers somewhere than the limits allow
+    ///
+    /// # Arguments
+    /// * `limits` - The limits
+    /// * `sudoku` - A reference to the Sudoku grid, with the fields still filled in
+    /// * `fields` - The fields to empty, as (row, column)
+    fn breaks_minimum(&self, limits: &GivenLimits, sudoku: &Grid, fields: &[(usize, usize)]) -> bool {
+        let after = self.without(sudoku, fields);
+        limits.ranges().iter().zip(after.counts().iter().zip(self.counts())).any(|(range, (after, before))| {
+            // a count that was already too low may stay as it is, but not get any lower
+            after.iter().zip(before).any(|(&after, &before)| after < range.min && after < before)
+        })
+    }
+
+    /// Whether emptying the fields would take a given number away from a row, column, subgrid or
+    /// number which has more given numbers than the limits allow
+    ///
+    /// # Arguments
+    /// * `limits` - The limits
+    /// * `sudoku` - A reference to the Sudoku grid, with the fields still filled in
+    /// * `fields` - The fields to empty, as (row, column)
+    fn reduces_excess(&self, limits: &GivenLimits, sudoku: &Grid, fields: &[(usize, usize)]) -> bool {
+        let after = self.without(sudoku, fields);
+        limits.ranges().iter().zip(after.counts().iter().zip(self.counts())).any(|(range, (after, before))| {
+            after.iter().zip(before).any(|(&after, &before)| before > range.max && after < before)
+        })
+    }
+}
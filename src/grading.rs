@@ -0,0 +1,152 @@
+/*
+    Grading : Judges how hard a sudoku is for a person to solve. The number of empty fields says
+    little about this, so instead the sudoku is solved with the logical solver, and the sudoku is
+    rated by the hardest technique that was needed. The ratings follow the scale of Sudoku
+    Explainer, from 1.5 for a hidden single up to 7.0 for chains, and 10.0 for sudokus which the
+    techniques can't solve at all.
+ */
+
+use std::fmt;
+
+use crate::logical_solver::{solve_logically, LogicalSolveError, Technique};
+use crate::grid::Grid;
+
+
+/// The rating of sudokus that can't be solved with any of the techniques
+const UNSOLVED_RATING: f64 = 10.0;
+
+/// How hard a sudoku is, from easiest to hardest
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub enum Difficulty {
+    /// Only singles are needed
+    Easy,
+    /// Pointing and claiming are needed as well
+    Medium,
+    /// Pairs, triples, X-Wings or Swordfish are needed
+    Hard,
+    /// Wings, unique rectangles, quads or Jellyfish are needed
+    Expert,
+    /// Colouring or chains are needed, or the sudoku can't be solved with techniques at all
+    Diabolical,
+}
+
+impl Difficulty {
+    /// All difficulties, from easiest to hardest
+    pub const ALL: [Difficulty; 5] = [
+        Difficulty::Easy,
+        Difficulty::Medium,
+        Difficulty::Hard,
+        Difficulty::Expert,
+        Difficulty::Diabolical,
+    ];
+
+    /// Returns the difficulty that belongs to a rating
+    ///
+    /// # Arguments
+    /// * `score` - The rating of the sudoku, as given by `technique_rating`
+    pub fn from_score(score: f64) -> Difficulty {
+        if score <= 2.3 {
+            Difficulty::Easy
+        } else if score <= 2.8 {
+            Difficulty::Medium
+        } else if score <= 4.0 {
+            Difficulty::Hard
+        } else if score <= 6.0 {
+            Difficulty::Expert
+        } else {
+            Difficulty::Diabolical
+        }
+    }
+}
+
+impl fmt::Display for Difficulty {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let name = match self {
+            Difficulty::Easy => "Easy",
+            Difficulty::Medium => "Medium",
+            Difficulty::Hard => "Hard",
+            Difficulty::Expert => "Expert",
+            Difficulty::Diabolical => "Diabolical",
+        };
+        write!(f, "{}", name)
+    }
+}
+
+/// Returns how hard a single technique is, on the scale of Sudoku Explainer
+///
+/// # Arguments
+/// * `technique` - The technique to rate
+pub fn technique_rating(technique: Technique) -> f64 {
+    match technique {
+        Technique::HiddenSingle => 1.5,
+        Technique::NakedSingle => 2.3,
+        Technique::Pointing => 2.6,
+        Technique::Claiming => 2.8,
+        Technique::NakedPair => 3.0,
+        Technique::XWing => 3.2,
+        Technique::HiddenPair => 3.4,
+        Technique::NakedTriple => 3.6,
+        Technique::Swordfish => 3.8,
+        Technique::HiddenTriple => 4.0,
+        Technique::XYWing => 4.2,
+        Technique::XYZWing => 4.4,
+        Technique::WWing => 4.4,
+        Technique::UniqueRectangle => 4.5,
+        Technique::NakedQuad => 5.0,
+        Technique::Jellyfish => 5.2,
+        Technique::HiddenQuad => 5.4,
+        Technique::SimpleColouring => 6.2,
+        Technique::Chain => 7.0,
+    }
+}
+
+/// How hard a sudoku is, and why
+#[derive(Clone, Debug, PartialEq)]
+pub struct Grade {
+    pub difficulty: Difficulty,
+    /// The rating of the hardest technique that was needed (or 10.0 if the techniques got stuck)
+    pub score: f64,
+    /// The hardest technique that was needed, if any. None if the techniques couldn't solve the
+    /// sudoku, as then the techniques that were used are not what makes it hard.
+    pub hardest_technique: Option<Technique>,
+    /// The number of steps the logical solver took
+    pub steps: usize,
+    /// Whether the techniques were enough to solve the sudoku
+    pub solved_logically: bool,
+}
+
+impl fmt::Display for Grade {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self.hardest_technique {
+            _ if !self.solved_logically =>
+                write!(f, "{} ({:.1}, the techniques alone can't solve it)", self.difficulty, self.score),
+            Some(technique) => write!(f, "{} ({:.1}, hardest technique: {})", self.difficulty, self.score, technique),
+            None => write!(f, "{} ({:.1})", self.difficulty, self.score),
+        }
+    }
+}
+
+/// Grades a sudoku, by solving it with the logical solver and rating the hardest technique it
+/// needed. This works for any sudoku, generated or not, of up to 64x64 (`MAX_LOGICAL_SIZE`);
+/// wider sudokus are rejected with an error.
+///
+/// # Arguments
+/// * `sudoku` - A reference to the Sudoku grid.
+pub fn grade_sudoku(sudoku: &Grid) -> Result<Grade, LogicalSolveError> {
+    let solution = solve_logically(sudoku)?;
+
+    let (hardest_technique, score) = if solution.solved {
+        let hardest_technique = solution.steps.iter().map(|step| step.technique).max();
+        (hardest_technique, hardest_technique.map_or(0.0, technique_rating))
+    } else {
+        (None, UNSOLVED_RATING)
+    };
+
+    Ok(Grade {
+        difficulty: Difficulty::from_score(score),
+        score,
+        hardest_technique,
+        steps: solution.steps.len(),
+        solved_logically: solution.solved,
+    })
+}
@@ -0,0 +1,375 @@
+/*
+    Grid : The sudoku grid itself, together with the shape of its subgrids. The fields are stored
+    row by row in a single vector, where every field is either empty (None) or holds a number
+    between 1 and the width of the sudoku. A grid can only be made through constructors that check
+    this, so the rest of the code never has to deal with ragged rows or numbers that don't fit.
+ */
+
+use std::fmt;
+use num::integer::sqrt;         // square root
+
+
+/// A number in a field of the sudoku, from 1 up to the width of the sudoku
+pub type Digit = u8;
+
+/// The shape of a single subgrid of a sudoku. A sudoku with subgrids of `rows` x `columns` has a
+/// width and height of `rows * columns`, has `columns` subgrids stacked on top of each other and
+/// `rows` subgrids next to each other.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub struct BoxShape {
+    pub rows: usize,
+    pub columns: usize,
+}
+
+impl BoxShape {
+    /// Returns the shape of a subgrid with the given number of rows and columns
+    ///
+    /// # Arguments
+    /// * `rows` - The height of a subgrid
+    /// * `columns` - The width of a subgrid
+    pub fn new(rows: usize, columns: usize) -> BoxShape {
+        BoxShape { rows, columns }
+    }
+
+    /// Returns the standard subgrid shape for a sudoku of the given width. This is the square
+    /// subgrid for 4x4, 9x9, 16x16..., and otherwise the subgrid closest to a square that is wider
+    /// than it is high (2x3 for 6x6, 2x4 for 8x8, 3x4 for 12x12). Returns None when the width can
+    /// only be split into subgrids which are a single row high.
+    ///
+    /// # Arguments
+    /// * `size` - The width (and height) of the sudoku
+    pub fn for_size(size: usize) -> Option<BoxShape> {
+        if size == 1 {
+            return Some(BoxShape::new(1, 1));
+        }
+
+        // the largest divisor which is not larger than the square root gives the squarest shape
+        let rows = (2..=sqrt(size)).rev().find(|&rows| size.is_multiple_of(rows))?;
+        Some(BoxShape::new(rows, size / rows))
+    }
+
+    /// The width and height of a sudoku with subgrids of this shape
+    pub fn size(&self) -> usize {
+        self.rows * self.columns
+    }
+
+    /// Whether the subgrid is as wide as it is high
+    pub fn is_square(&self) -> bool {
+        self.rows == self.columns
+    }
+
+    /// The shape of the subgrids after turning the sudoku by 90 (or 270) degrees
+    pub fn rotated(&self) -> BoxShape {
+        BoxShape::new(self.columns, self.rows)
+    }
+
+    /// Returns the number of the subgrid which contains the field. Subgrids are numbered row by
+    /// row, starting with 0 in the top left.
+    ///
+    /// # Arguments
+    /// * `row` - The row of the field
+    /// * `column` - The column of the field
+    pub fn subgrid_of(&self, row: usize, column: usize) -> usize {
+        (row / self.rows) * self.rows + column / self.columns
+    }
+
+    /// Returns the locations (row, column) of all fields inside of a subgrid
+    ///
+    /// # Arguments
+    /// * `subgrid` - The number of the subgrid (see `subgrid_of`)
+    pub fn subgrid_fields(&self, subgrid: usize) -> impl Iterator<Item = (usize, usize)> {
+        let top = (subgrid / self.rows) * self.rows;
+        let left = (subgrid % self.rows) * self.columns;
+        let columns = self.columns;
+        (top..top + self.rows).flat_map(move |row| (left..left + columns).map(move |column| (row, column)))
+    }
+}
+
+/// The reasons why a grid can't be made from the given values
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum GridError {
+    /// The grid doesn't have as many rows as belong to the shape of the subgrids
+    WrongSize { expected: usize, rows: usize },
+    /// A row doesn't have as many fields as belong to the shape of the subgrids
+    RaggedRow { row: usize, expected: usize, columns: usize },
+    /// A field contains a number which can't be used in a sudoku of this size
+    InvalidNumber { row: usize, column: usize, value: i32 },
+    /// The sudoku is wider than the largest number a field can hold
+    TooLarge { size: usize, max: usize },
+}
+
+impl fmt::Display for GridError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            GridError::WrongSize { expected, rows } =>
+                write!(f, "expected a {}x{} sudoku, got {} rows", expected, expected, rows),
+            GridError::RaggedRow { row, expected, columns } =>
+                write!(f, "expected {} fields in row {}, got {}", expected, row, columns),
+            GridError::InvalidNumber { row, column, value } =>
+                write!(f, "invalid number {} in row {}, column {}", value, row, column),
+            GridError::TooLarge { size, max } =>
+                write!(f, "a {}x{} sudoku is too large, a sudoku can be at most {} wide", size, size, max),
+        }
+    }
+}
+
+impl std::error::Error for GridError {}
+
+/// A sudoku grid. Rows and columns start counting at 0, and every field is either empty (None) or
+/// holds a number between 1 and the width of the sudoku.
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+pub struct Grid {
+    box_shape: BoxShape,
+    /// The fields, row by row, so the field in (row, column) is at row * size + column
+    fields: Vec<Option<Digit>>,
+}
+
+impl Grid {
+    /// Returns a grid without any numbers filled in
+    ///
+    /// # Arguments
+    /// * `box_shape` - The shape of the subgrids of the sudoku
+    ///
+    /// # Panics
+    ///
+    /// Panics if the sudoku would be wider than the largest `Digit`.
+    pub fn empty(box_shape: BoxShape) -> Grid {
+        let size = box_shape.size();
+        assert!(size <= Digit::MAX as usize, "a sudoku can be at most {} wide", Digit::MAX);
+        Grid { box_shape, fields: vec![None; size * size] }
+    }
+
+    /// Makes a grid from rows of numbers, where 0 stands for an empty field. Returns an error if
+    /// the rows don't fit the shape of the subgrids, contain numbers outside of 0 to width, or if
+    /// the sudoku is wider than the largest `Digit`.
+    ///
+    /// # Arguments
+    /// * `rows` - The rows of the sudoku, from top to bottom
+    /// * `box_shape` - The shape of the subgrids of the sudoku
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use sudoku_creator::{BoxShape, Grid, GridError};
+    ///
+    /// let grid = Grid::from_rows(&[vec![1, 0], vec![0, 1]], BoxShape::new(1, 2)).unwrap();
+    /// assert_eq!(grid.get(0, 0), Some(1));
+    /// assert_eq!(grid.get(0, 1), None);
+    ///
+    /// let ragged = Grid::from_rows(&[vec![1, 0], vec![0]], BoxShape::new(1, 2));
+    /// assert_eq!(ragged, Err(GridError::RaggedRow { row: 1, expected: 2, columns: 1 }));
+    /// ```
+    pub fn from_rows(rows: &[Vec<i32>], box_shape: BoxShape) -> Result<Grid, GridError> {
+        let size = box_shape.size();
+        if size > Digit::MAX as usize {
+            return Err(GridError::TooLarge { size, max: Digit::MAX as usize });
+        }
+        if rows.len() != size {
+            return Err(GridError::WrongSize { expected: size, rows: rows.len() });
+        }
+
+        let mut grid = Grid::empty(box_shape);
+        for (row, values) in rows.iter().enumerate() {
+            if values.len() != size {
+                return Err(GridError::RaggedRow { row, expected: size, columns: values.len() });
+            }
+            for (column, &value) in values.iter().enumerate() {
+                if value < 0 || value > size as i32 {
+                    return Err(GridError::InvalidNumber { row, column, value });
+                }
+                if value != 0 {
+                    grid.set(row, column, Some(value as Digit));
+                }
+            }
+        }
+        Ok(grid)
+    }
+
+    /// Returns the rows of the grid as numbers, with 0 for the empty fields
+    pub fn to_rows(&self) -> Vec<Vec<i32>> {
+        self.rows()
+            .map(|row| row.map(|value| value.map_or(0, i32::from)).collect())
+            .collect()
+    }
+
+    /// The width and height of the sudoku
+    pub fn size(&self) -> usize {
+        self.box_shape.size()
+    }
+
+    /// The shape of the subgrids of the sudoku
+    pub fn box_shape(&self) -> BoxShape {
+        self.box_shape
+    }
+
+    /// Returns the number in a field, or None if it is empty
+    ///
+    /// # Arguments
+    /// * `row` - The row of the field
+    /// * `column` - The column of the field
+    ///
+    /// # Panics
+    ///
+    /// Panics if the row or the column is outside of the sudoku.
+    pub fn get(&self, row: usize, column: usize) -> Option<Digit> {
+        self.fields[self.index(row, column)]
+    }
+
+    /// Fills in a field, or empties it with None
+    ///
+    /// # Arguments
+    /// * `row` - The row of the field
+    /// * `column` - The column of the field
+    /// * `value` - The number to fill in, or None to empty the field
+    ///
+    /// # Panics
+    ///
+    /// Panics if the row or the column is outside of the sudoku, or if the number is 0 or larger
+    /// than the width of the sudoku.
+    pub fn set(&mut self, row: usize, column: usize, value: Option<Digit>) {
+        if let Some(number) = value {
+            assert!(number >= 1 && number as usize <= self.size(), "{} doesn't fit in a {}x{} sudoku", number, self.size(), self.size());
+        }
+        let index = self.index(row, column);
+        self.fields[index] = value;
+    }
+
+    /// Returns where a field is stored in the list of fields
+    ///
+    /// # Arguments
+    /// * `row` - The row of the field
+    /// * `column` - The column of the field
+    ///
+    /// # Panics
+    ///
+    /// Panics if the row or the column is outside of the sudoku.
+    fn index(&self, row: usize, column: usize) -> usize {
+        let size = self.size();
+        assert!(row < size && column < size, "there is no field ({}, {}) in a {}x{} sudoku", row, column, size, size);
+        row * size + column
+    }
+
+    /// Returns the values of a row, from left to right
+    ///
+    /// # Arguments
+    /// * `row` - The row index
+    pub fn row(&self, row: usize) -> impl Iterator<Item = Option<Digit>> + '_ {
+        let size = self.size();
+        self.fields[row * size..(row + 1) * size].iter().copied()
+    }
+
+    /// Returns the values of a column, from top to bottom
+    ///
+    /// # Arguments
+    /// * `column` - The column index
+    pub fn column(&self, column: usize) -> impl Iterator<Item = Option<Digit>> + '_ {
+        self.fields.iter().skip(column).step_by(self.size()).copied()
+    }
+
+    /// Returns the values of a subgrid, row by row
+    ///
+    /// # Arguments
+    /// * `subgrid` - The number of the subgrid (see `BoxShape::subgrid_of`)
+    pub fn subgrid(&self, subgrid: usize) -> impl Iterator<Item = Option<Digit>> + '_ {
+        self.box_shape.subgrid_fields(subgrid).map(move |(row, column)| self.get(row, column))
+    }
+
+    /// Returns all rows, from top to bottom
+    pub fn rows(&self) -> impl Iterator<Item = impl Iterator<Item = Option<Digit>> + '_> + '_ {
+        (0..self.size()).map(move |row| self.row(row))
+    }
+
+    /// Returns all columns, from left to right
+    pub fn columns(&self) -> impl Iterator<Item = impl Iterator<Item = Option<Digit>> + '_> + '_ {
+        (0..self.size()).map(move |column| self.column(column))
+    }
+
+    /// Returns all subgrids, row by row
+    pub fn subgrids(&self) -> impl Iterator<Item = impl Iterator<Item = Option<Digit>> + '_> + '_ {
+        (0..self.size()).map(move |subgrid| self.subgrid(subgrid))
+    }
+
+    /// Returns every field as (row, column, value), row by row
+    pub fn fields(&self) -> impl Iterator<Item = (usize, usize, Option<Digit>)> + '_ {
+        let size = self.size();
+        self.fields.iter().enumerate().map(move |(index, &value)| (index / size, index % size, value))
+    }
+
+    /// Returns the locations (row, column) of all empty fields, row by row
+    pub fn empty_fields(&self) -> Vec<(usize, usize)> {
+        self.fields()
+            .filter(|(_, _, value)| value.is_none())
+            .map(|(row, column, _)| (row, column))
+            .collect()
+    }
+
+    /// The number of fields which are filled in
+    pub fn count_filled(&self) -> usize {
+        self.fields.iter().filter(|value| value.is_some()).count()
+    }
+
+    /// Returns all of the numbers which can still be put inside a field, whilst still following
+    /// the rules of sudoku (no duplicates in row, column or subgrid)
+    ///
+    /// # Arguments
+    /// * `row` - The row of the field
+    /// * `column` - The column of the field
+    pub fn candidates(&self, row: usize, column: usize) -> Vec<Digit> {
+        let subgrid = self.box_shape.subgrid_of(row, column);
+        let mut used = vec![false; self.size() + 1];
+        for value in self.row(row).chain(self.column(column)).chain(self.subgrid(subgrid)).flatten() {
+            used[value as usize] = true;
+        }
+        (1..=self.size() as Digit).filter(|&number| !used[number as usize]).collect()
+    }
+
+    /// Returns True/False, based on whether all rules are followed for a specific field. The rules
+    /// are no duplicates in rows, columns or within the subgrid
+    ///
+    /// # Arguments
+    /// * `row` - The row of the field
+    /// * `column` - The column of the field
+    pub fn is_field_valid(&self, row: usize, column: usize) -> bool {
+        let subgrid = self.box_shape.subgrid_of(row, column);
+        has_no_duplicates(self.size(), self.row(row))
+            && has_no_duplicates(self.size(), self.column(column))
+            && has_no_duplicates(self.size(), self.subgrid(subgrid))
+    }
+
+    /// Returns True if all filled in fields follow the rules (no duplicates in rows, columns or
+    /// subgrids). Empty fields are ignored, so this also works on partly filled sudokus.
+    pub fn is_valid(&self) -> bool {
+        let size = self.size();
+        self.rows().all(|row| has_no_duplicates(size, row))
+            && self.columns().all(|column| has_no_duplicates(size, column))
+            && self.subgrids().all(|subgrid| has_no_duplicates(size, subgrid))
+    }
+
+    /// Returns True if all fields are filled in
+    pub fn is_full(&self) -> bool {
+        self.fields.iter().all(|value| value.is_some())
+    }
+
+    /// Returns True/False based on whether all fields are filled in, and follow all of the rules
+    /// (no duplicate numbers in rows, columns and subgrids)
+    pub fn is_solved(&self) -> bool {
+        // a full row without duplicates holds every number exactly once
+        self.is_full() && self.is_valid()
+    }
+}
+
+/// Returns True if no number appears twice among the values. Empty fields are ignored.
+///
+/// # Arguments
+/// * `size` - The width of the sudoku, which is the largest number that can appear
+/// * `values` - The values of a row, column or subgrid
+fn has_no_duplicates(size: usize, values: impl Iterator<Item = Option<Digit>>) -> bool {
+    let mut seen = vec![false; size + 1];
+    for number in values.flatten() {
+        if seen[number as usize] {
+            return false;
+        }
+        seen[number as usize] = true;
+    }
+    true
+}
@@ -0,0 +1,204 @@
+/*
+    Io : Showing sudokus to the outside world, and reading them back in. On a single line, a
+    sudoku is written row by row with one character per field: the digits 1 to 9, then the letters
+    A to Z for the numbers 10 to 35, and a dot (or a 0) for an empty field.
+ */
+
+use std::fmt;
+
+use crate::grid::{BoxShape, Digit, Grid};
+
+
+/// The characters for the numbers 1 to 35, on a single line
+const NUMBER_CHARACTERS: &str = "123456789ABCDEFGHIJKLMNOPQRSTUVWXYZ";
+
+/// The reasons why a line can't be read as a sudoku
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum ParseSudokuError {
+    /// The number of fields doesn't fit a sudoku (of the given shape)
+    WrongLength { fields: usize },
+    /// The sudoku is wider than the numbers that can be written on a single line
+    TooLarge { size: usize, max: usize },
+    /// There is no sudoku of this width with subgrids higher than a single row
+    NoBoxShape { size: usize },
+    /// A character which isn't a number or an empty field, at this position (counting from 0)
+    UnknownCharacter { character: char, position: usize },
+    /// A number that is larger than the width of the sudoku, at this position (counting from 0)
+    NumberTooLarge { character: char, position: usize },
+}
+
+impl fmt::Display for ParseSudokuError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ParseSudokuError::WrongLength { fields } =>
+                write!(f, "{} fields don't make a sudoku", fields),
+            ParseSudokuError::TooLarge { size, max } =>
+                write!(f, "sudokus of up to {}x{} can be read, not {}x{}", max, max, size, size),
+            ParseSudokuError::NoBoxShape { size } =>
+                write!(f, "there is no standard shape of subgrids for a {}x{} sudoku", size, size),
+            ParseSudokuError::UnknownCharacter { character, position } =>
+                write!(f, "unexpected {:?} at field {}", character, position + 1),
+            ParseSudokuError::NumberTooLarge { character, position } =>
+                write!(f, "{:?} at field {} is too large for this sudoku", character, position + 1),
+        }
+    }
+}
+
+impl std::error::Error for ParseSudokuError {}
+
+/// Reads a sudoku from a single line (see the top of this file). Spaces are skipped. Without a
+/// shape of the subgrids, the width is found from the number of fields and the standard shape for
+/// that width is used (see `BoxShape::for_size`). Sudokus wider than 35x35 are rejected, as there
+/// are no characters for larger numbers.
+///
+/// # Arguments
+/// * `line` - The line to read
+/// * `box_shape` - The shape of the subgrids, or None for the standard shape
+///
+/// # Example
+///
+/// ```
+/// use sudoku_creator::{format_sudoku_line, parse_sudoku};
+///
+/// let sudoku = parse_sudoku(".4....2..3....4.", None).unwrap();
+/// assert_eq!(sudoku.size(), 4);
+/// assert_eq!(sudoku.get(0, 1), Some(4));
+/// assert_eq!(format_sudoku_line(&sudoku), ".4....2..3....4.");
+/// ```
+pub fn parse_sudoku(line: &str, box_shape: Option<BoxShape>) -> Result<Grid, ParseSudokuError> {
+    let characters: Vec<char> = line.chars().filter(|character| !character.is_whitespace()).collect();
+    let fields = characters.len();
+    let size = (1..=fields).find(|size| size * size >= fields).unwrap_or(0);
+    if size * size != fields || box_shape.is_some_and(|shape| shape.size() != size) {
+        return Err(ParseSudokuError::WrongLength { fields });
+    }
+    if size > NUMBER_CHARACTERS.len() {
+        return Err(ParseSudokuError::TooLarge { size, max: NUMBER_CHARACTERS.len() });
+    }
+    let box_shape = box_shape.or_else(|| BoxShape::for_size(size)).ok_or(ParseSudokuError::NoBoxShape { size })?;
+
+    let mut sudoku = Grid::empty(box_shape);
+    for (position, &character) in characters.iter().enumerate() {
+        let value = match character {
+            '.' | '0' => None,
+            _ => {
+                let number = NUMBER_CHARACTERS.find(character.to_ascii_uppercase())
+                    .ok_or(ParseSudokuError::UnknownCharacter { character, position })? + 1;
+                if number > size {
+                    return Err(ParseSudokuError::NumberTooLarge { character, position });
+                }
+                Some(number as Digit)
+            }
+        };
+        sudoku.set(position / size, position % size, value);
+    }
+    Ok(sudoku)
+}
+
+/// Writes a sudoku on a single line (see the top of this file), which `parse_sudoku` reads back
+///
+/// # Arguments
+/// * `sudoku` - A reference to the Sudoku grid.
+///
+/// # Panics
+///
+/// Panics if the sudoku is wider than 35x35, as there are no characters for larger numbers.
+pub fn format_sudoku_line(sudoku: &Grid) -> String {
+    assert!(sudoku.size() <= NUMBER_CHARACTERS.len(), "only sudokus of up to 35x35 can be written on a line");
+    sudoku.fields().map(|(_, _, value)| match value {
+        None => '.',
+        Some(number) => NUMBER_CHARACTERS.as_bytes()[number as usize - 1] as char,
+    }).collect()
+}
+
+
+/// Formats a Sudoku grid as text, with every number taking up 4 characters and empty cells
+/// represented by spaces.
+///
+/// # Arguments
+///
+/// * `sudoku` - A reference to the Sudoku grid.
+///
+/// # Example
+///
+/// ```
+/// use sudoku_creator::{format_sudoku, BoxShape, Grid};
+///
+/// let sudoku_grid = Grid::from_rows(&[
+///     vec![1, 0, 3, 4],
+///     vec![3, 4, 1, 2],
+///     vec![2, 1, 4, 3],
+///     vec![4, 3, 0, 1],
+/// ], BoxShape::new(2, 2)).unwrap();
+///
+/// assert_eq!(format_sudoku(&sudoku_grid).lines().next(), Some("   1       3   4"));
+/// ```
+pub fn format_sudoku(sudoku : &Grid) -> String {
+    let mut text = String::new();
+    for row in sudoku.rows() {
+        for element in row {
+            match element {
+                None => text.push_str(&format!("{:4}", " ")),
+                Some(number) => text.push_str(&format!("{:4}", number)),
+            }
+        }
+        text.push('\n');
+    }
+    text
+}
+
+/// Prints a Sudoku grid.
+///
+/// # Arguments
+///
+/// * `sudoku` - A reference to the Sudoku grid.
+///
+/// # Example
+///
+/// ```
+/// use sudoku_creator::{print_sudoku, BoxShape, Grid};
+///
+/// let sudoku_grid = Grid::from_rows(&[
+///     vec![5, 3, 0, 0, 7, 0, 0, 0, 0],
+///     vec![6, 0, 0, 1, 9, 5, 0, 0, 0],
+///     vec![0, 9, 8, 0, 0, 0, 0, 6, 0],
+///     vec![8, 0, 0, 0, 6, 0, 0, 0, 3],
+///     vec![4, 0, 0, 8, 0, 3, 0, 0, 1],
+///     vec![7, 0, 0, 0, 2, 0, 0, 0, 6],
+///     vec![0, 6, 0, 0, 0, 0, 2, 8, 0],
+///     vec![0, 0, 0, 4, 1, 9, 0, 0, 5],
+///     vec![0, 0, 0, 0, 8, 0, 0, 7, 9],
+/// ], BoxShape::new(3, 3)).unwrap();
+///
+/// print_sudoku(&sudoku_grid);
+/// ```
+///
+/// This will print the Sudoku grid to the console, with empty cells represented by spaces.
+///
+/// ```text
+///    5   3           7
+///    6           1   9   5
+///        9   8                   6
+///    8               6               3
+///    4           8       3           1
+///    7               2               6
+///        6                   2   8
+///                4   1   9           5
+///                    8           7   9
+///
+/// ```
+///
+/// Note: In the printed output, empty cells are represented by spaces.
+///
+/// # Panics
+///
+/// This function does not panic under normal circumstances.
+pub fn print_sudoku(sudoku : &Grid) {
+    println!("{}", format_sudoku(sudoku));
+}
+
+impl fmt::Display for Grid {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", format_sudoku(self))
+    }
+}
@@ -0,0 +1,47 @@
+/*
+    Sudoku_creator : This is a collection of functions which allows us to generate both Sudokus
+    that we can solve, as well as their solutions
+
+    A sudoku is possible, as long as width = height and the width can be split into subgrids of
+    box_rows x box_columns, with box_rows * box_columns = width. The numbers used are 1 through
+    width. Square subgrids give the sudokus of size 1x1, 4x4, 9x9, 16x16, 25x25 and so on, but
+    rectangular subgrids are possible too, like 6x6 (2x3 subgrids), 8x8 (2x4) and 12x12 (3x4).
+
+    Author : Martijn Folmer
+    Date : 19-01-2024
+ */
+
+pub mod grid;                   // the sudoku grid, and the shape of its subgrids
+pub mod generator;              // generating filled in sudokus and sudokus to solve
+pub mod solver;                 // solving sudokus by backtracking
+mod bitmask_solver;             // the fast core of the solver, with the used numbers as bitmasks
+pub mod dlx;                    // solving sudokus as an exact cover problem, with dancing links
+pub mod logical_solver;         // solving sudokus step by step, with named techniques
+pub mod grading;                // judging how hard a sudoku is
+pub mod daily;                  // the puzzle of the day
+pub mod io;                     // printing and reading sudokus
+pub mod random;                 // seeded random numbers, so sudokus can be generated again
+pub mod transforms;             // changing sudokus without breaking them
+pub mod canonical;              // telling apart sudokus that are the same up to symmetry
+
+pub use canonical::{are_equivalent, canonical_form, canonicalize, canonicalize_puzzle, MAX_CANONICAL_SIZE};
+pub use daily::{daily_seed, generate_daily_puzzle, Date, ParseDateError};
+pub use generator::{
+    generate_full_sudoku, generate_full_sudoku_with_box_shape, generate_full_sudoku_with_method, generate_graded_sudoku,
+    generate_puzzle, generate_puzzle_from_mask, generate_puzzle_requiring, generate_puzzle_with, generate_puzzle_with_limits,
+    generate_sudoku_to_solve, generate_sudoku_to_solve_with, minimise, FillMethod, GenerationBudget, GenerationFailure,
+    GenerationTarget, GivenLimits, GivenRange, Puzzle, RemovalEnd, RemovalMode, RemovalOptions, RemovalReport, Symmetry,
+    MAX_RANDOM_FILL_SIZE,
+};
+pub use grading::{grade_sudoku, Difficulty, Grade};
+pub use grid::{BoxShape, Digit, Grid, GridError};
+pub use io::{format_sudoku, format_sudoku_line, parse_sudoku, print_sudoku, ParseSudokuError};
+pub use logical_solver::{
+    get_next_step, solve_logically, solve_logically_with, LogicalSolution, LogicalSolveError, Step, Technique, MAX_LOGICAL_SIZE,
+};
+pub use random::{random_seed, seeded_rng, SudokuRng};
+pub use solver::{
+    count_solutions, count_solutions_with, get_differing_fields, get_solution, get_solution_with, has_unique_solution,
+    is_minimal, solutions, solutions_with, solve_sudoku, solve_sudoku_with, SolveError, Solutions, SolverBackend,
+};
+pub use transforms::{Reflection, Transform, TransformError};
@@ -0,0 +1,1131 @@
+/*
+    Logical solver : Solves sudokus the way a person would, by applying named solving techniques
+    (naked singles, pointing, X-Wings, chains and so on) one step at a time, and recording every
+    step. Unlike the backtracking in solve_sudoku it never guesses, so the recorded steps can be
+    used to explain a sudoku, to give hints, or to judge how hard it is.
+
+    The candidates (the numbers which can still go into an empty field) are stored as bitmasks,
+    where bit 0 stands for the number 1, bit 1 for the number 2 and so on. This supports sudokus of
+    up to 64x64, wider ones are rejected with an error.
+ */
+
+use std::collections::{HashMap, VecDeque};
+use std::fmt;
+
+use crate::bitmask_solver;
+use crate::grid::{BoxShape, Digit, Grid};
+
+
+/// A named solving technique. The techniques are listed from easiest to hardest, which is also
+/// the order in which the logical solver tries them.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub enum Technique {
+    /// A number can only go in one field of a row, column or subgrid
+    HiddenSingle,
+    /// A field has only one number left that can go in it
+    NakedSingle,
+    /// Within a subgrid, a number is confined to one row or column, so it can be removed from the
+    /// rest of that row or column
+    Pointing,
+    /// Within a row or column, a number is confined to one subgrid, so it can be removed from the
+    /// rest of that subgrid
+    Claiming,
+    /// Two fields of a row, column or subgrid share the same two candidates
+    NakedPair,
+    /// A number is confined to the same two columns in two rows (or the other way around)
+    XWing,
+    /// Two numbers are confined to the same two fields of a row, column or subgrid
+    HiddenPair,
+    /// Three fields of a row, column or subgrid share three candidates between them
+    NakedTriple,
+    /// A number is confined to the same three columns in three rows (or the other way around)
+    Swordfish,
+    /// Three numbers are confined to the same three fields of a row, column or subgrid
+    HiddenTriple,
+    /// A field with candidates xy sees two fields with xz and yz, so z is removed from fields
+    /// which see both of them
+    XYWing,
+    /// A field with candidates xyz sees two fields with xz and yz, so z is removed from fields
+    /// which see all three
+    XYZWing,
+    /// Two fields with the same two candidates xy are connected by a row, column or subgrid in
+    /// which x can only go in two places
+    WWing,
+    /// Three corners of a rectangle over two subgrids hold the same two candidates, so the fourth
+    /// corner can't hold only those two (that would give two solutions)
+    UniqueRectangle,
+    /// Four fields of a row, column or subgrid share four candidates between them
+    NakedQuad,
+    /// A number is confined to the same four columns in four rows (or the other way around)
+    Jellyfish,
+    /// Four numbers are confined to the same four fields of a row, column or subgrid
+    HiddenQuad,
+    /// The fields linked by a number that goes in exactly two places of a row, column or subgrid
+    /// are coloured in two alternating colours, one of which must be the true one
+    SimpleColouring,
+    /// An alternating chain of strong links (one of the two must be true) and weak links (not
+    /// both can be true) between candidates
+    Chain,
+}
+
+impl Technique {
+    /// All techniques, from easiest to hardest
+    pub const ALL: [Technique; 19] = [
+        Technique::HiddenSingle,
+        Technique::NakedSingle,
+        Technique::Pointing,
+        Technique::Claiming,
+        Technique::NakedPair,
+        Technique::XWing,
+        Technique::HiddenPair,
+        Technique::NakedTriple,
+        Technique::Swordfish,
+        Technique::HiddenTriple,
+        Technique::XYWing,
+        Technique::XYZWing,
+        Technique::WWing,
+        Technique::UniqueRectangle,
+        Technique::NakedQuad,
+        Technique::Jellyfish,
+        Technique::HiddenQuad,
+        Technique::SimpleColouring,
+        Technique::Chain,
+    ];
+
+    /// The name of the technique, as it is commonly known
+    pub fn name(&self) -> &'static str {
+        match self {
+            Technique::HiddenSingle => "Hidden single",
+            Technique::NakedSingle => "Naked single",
+            Technique::Pointing => "Pointing",
+            Technique::Claiming => "Claiming",
+            Technique::NakedPair => "Naked pair",
+            Technique::XWing => "X-Wing",
+            Technique::HiddenPair => "Hidden pair",
+            Technique::NakedTriple => "Naked triple",
+            Technique::Swordfish => "Swordfish",
+            Technique::HiddenTriple => "Hidden triple",
+            Technique::XYWing => "XY-Wing",
+            Technique::XYZWing => "XYZ-Wing",
+            Technique::WWing => "W-Wing",
+            Technique::UniqueRectangle => "Unique rectangle",
+            Technique::NakedQuad => "Naked quad",
+            Technique::Jellyfish => "Jellyfish",
+            Technique::HiddenQuad => "Hidden quad",
+            Technique::SimpleColouring => "Simple colouring",
+            Technique::Chain => "Chain",
+        }
+    }
+}
+
+impl fmt::Display for Technique {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.name())
+    }
+}
+
+/// A number in a particular field of the sudoku. Rows and columns start counting at 0.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub struct Candidate {
+    pub row: usize,
+    pub column: usize,
+    pub number: Digit,
+}
+
+/// A single application of a technique: the numbers it fills in, and the candidates it removes
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Step {
+    pub technique: Technique,
+    /// The numbers which are filled in by this step
+    pub placements: Vec<Candidate>,
+    /// The candidates which are removed by this step
+    pub eliminations: Vec<Candidate>,
+    /// A description of the step, meant for people
+    pub explanation: String,
+}
+
+/// The result of solving a sudoku with the logical solver
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct LogicalSolution {
+    /// The sudoku, filled in as far as the techniques got
+    pub sudoku: Grid,
+    /// Every step that was taken, in order
+    pub steps: Vec<Step>,
+    /// Whether the sudoku was completely filled in
+    pub solved: bool,
+}
+
+/// The widest sudoku the logical solver can solve, as the candidates are stored in 64 bits
+pub const MAX_LOGICAL_SIZE: usize = 64;
+
+/// The reasons why the logical solver can't work on a sudoku
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum LogicalSolveError {
+    /// The sudoku is wider than `MAX_LOGICAL_SIZE`
+    TooLarge { size: usize, max: usize },
+}
+
+impl fmt::Display for LogicalSolveError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            LogicalSolveError::TooLarge { size, max } =>
+                write!(f, "the logical solver supports sudokus of up to {}x{}, not {}x{}", max, max, size, size),
+        }
+    }
+}
+
+impl std::error::Error for LogicalSolveError {}
+
+/// Returns an error if the logical solver can't work on a sudoku of this shape
+///
+/// # Arguments
+/// * `box_shape` - The shape of the subgrids of the sudoku
+pub(crate) fn check_logical_size(box_shape: BoxShape) -> Result<(), LogicalSolveError> {
+    let size = box_shape.size();
+    if size > MAX_LOGICAL_SIZE {
+        return Err(LogicalSolveError::TooLarge { size, max: MAX_LOGICAL_SIZE });
+    }
+    Ok(())
+}
+
+/// Solves a sudoku using all techniques, and returns every step that was taken. If the
+/// techniques run out before the sudoku is filled in, the partly solved sudoku is returned with
+/// `solved` set to false. Sudokus wider than `MAX_LOGICAL_SIZE` are rejected with an error.
+///
+/// The unique rectangle technique assumes the sudoku has exactly one solution, like published
+/// sudokus do.
+///
+/// # Arguments
+/// * `sudoku` - A reference to the Sudoku grid.
+pub fn solve_logically(sudoku: &Grid) -> Result<LogicalSolution, LogicalSolveError> {
+    solve_logically_with(sudoku, &Technique::ALL)
+}
+
+/// Solves a sudoku using only the given techniques, and returns every step that was taken.
+/// Sudokus wider than `MAX_LOGICAL_SIZE` are rejected with an error.
+///
+/// # Arguments
+/// * `sudoku` - A reference to the Sudoku grid.
+/// * `techniques` - The techniques that may be used, in any order
+pub fn solve_logically_with(sudoku: &Grid, techniques: &[Technique]) -> Result<LogicalSolution, LogicalSolveError> {
+    check_logical_size(sudoku.box_shape())?;
+    let mut state = SolverState::new(sudoku);
+    let mut steps = vec![];
+
+    // always try the easiest technique first, and start over after every step
+    while !state.is_solved() && !state.is_broken() {
+        let step = Technique::ALL.iter()
+            .filter(|technique| techniques.contains(technique))
+            .find_map(|&technique| state.find_step(technique));
+
+        match step {
+            Some(step) => {
+                state.apply(&step);
+                steps.push(step);
+            }
+            None => break,
+        }
+    }
+
+    Ok(LogicalSolution {
+        sudoku: state.to_sudoku(),
+        solved: state.is_solved() && !state.is_broken(),
+        steps,
+    })
+}
+
+/// Returns the easiest step that can be taken next, which makes a good hint. Returns None if no
+/// technique applies (or the sudoku is already solved). Sudokus wider than `MAX_LOGICAL_SIZE` are
+/// rejected with an error.
+///
+/// # Arguments
+/// * `sudoku` - A reference to the Sudoku grid.
+pub fn get_next_step(sudoku: &Grid) -> Result<Option<Step>, LogicalSolveError> {
+    check_logical_size(sudoku.box_shape())?;
+    let state = SolverState::new(sudoku);
+    if state.is_broken() {
+        return Ok(None);
+    }
+    Ok(Technique::ALL.iter().find_map(|&technique| state.find_step(technique)))
+}
+
+
+/// A candidate in a chain, as (field, number), together with whether the chain makes it true
+type ChainNode = ((usize, usize), bool);
+
+/// Returns the numbers whose bits are set in the mask, from small to large
+///
+/// # Arguments
+/// * `mask` - The numbers as a bitmask, with bit 0 for the number 1
+fn numbers_in(mask: u64) -> impl Iterator<Item = usize> {
+    (0..64).filter(move |bit| mask & (1 << bit) != 0).map(|bit| bit + 1)
+}
+
+/// Returns the bitmask for a single number
+///
+/// # Arguments
+/// * `number` - The number, starting at 1
+fn bit(number: usize) -> u64 {
+    1 << (number - 1)
+}
+
+/// Returns all ways to pick `k` items out of `items`, keeping their order
+///
+/// # Arguments
+/// * `items` - The items to pick from
+/// * `k` - How many items to pick
+fn combinations(items: &[usize], k: usize) -> Vec<Vec<usize>> {
+    if k == 0 {
+        return vec![vec![]];
+    }
+    let mut result = vec![];
+    for (i, &item) in items.iter().enumerate() {
+        for mut rest in combinations(&items[i + 1..], k - 1) {
+            rest.insert(0, item);
+            result.push(rest);
+        }
+    }
+    result
+}
+
+/// The state of the logical solver: the filled in numbers and the candidates of all empty fields.
+/// Fields are numbered row by row, so field = row * size + column. The units (the rows, columns
+/// and subgrids) are numbered with the rows first, then the columns and then the subgrids.
+struct SolverState {
+    size: usize,
+    box_shape: BoxShape,
+    /// The number in each field, 0 for empty
+    values: Vec<usize>,
+    /// The candidates of each field, 0 for filled in fields
+    candidates: Vec<u64>,
+    /// The fields of every row, column and subgrid
+    units: Vec<Vec<usize>>,
+    /// The fields that share a row, column or subgrid with each field
+    peers: Vec<Vec<usize>>,
+    /// Set when the filled in numbers already break the rules
+    invalid_givens: bool,
+}
+
+impl SolverState {
+    /// Returns the state of a sudoku, with the candidates of every empty field worked out from
+    /// the filled in numbers
+    ///
+    /// # Arguments
+    /// * `sudoku` - A reference to the Sudoku grid, which must be at most `MAX_LOGICAL_SIZE` wide
+    fn new(sudoku: &Grid) -> SolverState {
+        let box_shape = sudoku.box_shape();
+        let size = box_shape.size();
+        let all_numbers = bitmask_solver::all_numbers(size);
+
+        let mut units = vec![];
+        for row in 0..size {
+            units.push((0..size).map(|column| row * size + column).collect());
+        }
+        for column in 0..size {
+            units.push((0..size).map(|row| row * size + column).collect());
+        }
+        for subgrid in 0..size {
+            units.push(box_shape.subgrid_fields(subgrid).map(|(row, column)| row * size + column).collect());
+        }
+
+        let mut state = SolverState {
+            size,
+            box_shape,
+            values: sudoku.fields().map(|(_, _, value)| value.map_or(0, usize::from)).collect(),
+            candidates: vec![0; size * size],
+            units,
+            peers: vec![],
+            invalid_givens: !sudoku.is_valid(),
+        };
+
+        state.peers = (0..size * size)
+            .map(|field| (0..size * size).filter(|&other| state.sees(field, other)).collect())
+            .collect();
+
+        for field in 0..size * size {
+            if state.values[field] == 0 {
+                let used = state.peers[field].iter()
+                    .filter(|&&peer| state.values[peer] != 0)
+                    .fold(0, |used, &peer| used | bit(state.values[peer]));
+                state.candidates[field] = all_numbers & !used;
+            }
+        }
+        state
+    }
+
+    /// Returns the row of a field
+    ///
+    /// # Arguments
+    /// * `field` - The field, numbered row by row
+    fn row_of(&self, field: usize) -> usize {
+        field / self.size
+    }
+
+    /// Returns the column of a field
+    ///
+    /// # Arguments
+    /// * `field` - The field, numbered row by row
+    fn column_of(&self, field: usize) -> usize {
+        field % self.size
+    }
+
+    /// Returns the subgrid of a field, counting the subgrids row by row from 0
+    ///
+    /// # Arguments
+    /// * `field` - The field, numbered row by row
+    fn subgrid_of(&self, field: usize) -> usize {
+        self.box_shape.subgrid_of(self.row_of(field), self.column_of(field))
+    }
+
+    /// Returns the units (row, column and subgrid) that contain the field
+    ///
+    /// # Arguments
+    /// * `field` - The field, numbered row by row
+    fn units_of(&self, field: usize) -> [usize; 3] {
+        [self.row_of(field), self.size + self.column_of(field), 2 * self.size + self.subgrid_of(field)]
+    }
+
+    /// Returns True if two different fields share a row, column or subgrid
+    ///
+    /// # Arguments
+    /// * `field1` - The first field
+    /// * `field2` - The second field
+    fn sees(&self, field1: usize, field2: usize) -> bool {
+        field1 != field2
+            && (self.row_of(field1) == self.row_of(field2)
+                || self.column_of(field1) == self.column_of(field2)
+                || self.subgrid_of(field1) == self.subgrid_of(field2))
+    }
+
+    /// Returns True if the number is still a candidate of the field
+    ///
+    /// # Arguments
+    /// * `field` - The field, numbered row by row
+    /// * `number` - The number to look for
+    fn has_candidate(&self, field: usize, number: usize) -> bool {
+        self.candidates[field] & bit(number) != 0
+    }
+
+    /// Returns the fields of a unit in which the number is still a candidate
+    ///
+    /// # Arguments
+    /// * `unit` - The row, column or subgrid, numbered as in `units`
+    /// * `number` - The number to look for
+    fn positions(&self, unit: usize, number: usize) -> Vec<usize> {
+        self.units[unit].iter().copied().filter(|&field| self.has_candidate(field, number)).collect()
+    }
+
+    /// Returns True if the number is already filled in somewhere in the unit
+    ///
+    /// # Arguments
+    /// * `unit` - The row, column or subgrid, numbered as in `units`
+    /// * `number` - The number to look for
+    fn is_placed_in(&self, unit: usize, number: usize) -> bool {
+        self.units[unit].iter().any(|&field| self.values[field] == number)
+    }
+
+    /// Returns True if every field is filled in
+    fn is_solved(&self) -> bool {
+        self.values.iter().all(|&value| value != 0)
+    }
+
+    /// Returns True if the sudoku can no longer be solved: the givens break the rules, an empty
+    /// field has no candidates left, or a number has no place left in a row, column or subgrid
+    fn is_broken(&self) -> bool {
+        if self.invalid_givens {
+            return true;
+        }
+        if (0..self.size * self.size).any(|field| self.values[field] == 0 && self.candidates[field] == 0) {
+            return true;
+        }
+        (0..self.units.len()).any(|unit| {
+            (1..=self.size).any(|number| !self.is_placed_in(unit, number) && self.positions(unit, number).is_empty())
+        })
+    }
+
+    /// Returns the filled in numbers as a grid, with the fields that are still open left empty
+    fn to_sudoku(&self) -> Grid {
+        let mut sudoku = Grid::empty(self.box_shape);
+        for (field, &value) in self.values.iter().enumerate() {
+            if value != 0 {
+                sudoku.set(self.row_of(field), self.column_of(field), Some(value as Digit));
+            }
+        }
+        sudoku
+    }
+
+    /// Returns the candidate for a number in a field, by row and column
+    ///
+    /// # Arguments
+    /// * `field` - The field, numbered row by row
+    /// * `number` - The number of the candidate
+    fn candidate(&self, field: usize, number: usize) -> Candidate {
+        Candidate { row: self.row_of(field), column: self.column_of(field), number: number as Digit }
+    }
+
+    /// Fills in the placements of a step, removing their numbers from the candidates of every
+    /// peer, and removes the eliminated candidates
+    ///
+    /// # Arguments
+    /// * `step` - The step to take
+    fn apply(&mut self, step: &Step) {
+        for placement in &step.placements {
+            let field = placement.row * self.size + placement.column;
+            let number = placement.number as usize;
+            self.values[field] = number;
+            self.candidates[field] = 0;
+            for &peer in &self.peers[field] {
+                self.candidates[peer] &= !bit(number);
+            }
+        }
+        for elimination in &step.eliminations {
+            let field = elimination.row * self.size + elimination.column;
+            self.candidates[field] &= !bit(elimination.number as usize);
+        }
+    }
+
+    /// Returns the name of a field as used in the explanations, like r1c5
+    ///
+    /// # Arguments
+    /// * `field` - The field, numbered row by row
+    fn field_name(&self, field: usize) -> String {
+        format!("r{}c{}", self.row_of(field) + 1, self.column_of(field) + 1)
+    }
+
+    /// Returns the name of a unit as used in the explanations, like row 3 or box 7
+    ///
+    /// # Arguments
+    /// * `unit` - The row, column or subgrid, numbered as in `units`
+    fn unit_name(&self, unit: usize) -> String {
+        match unit / self.size {
+            0 => format!("row {}", unit + 1),
+            1 => format!("column {}", unit - self.size + 1),
+            _ => format!("box {}", unit - 2 * self.size + 1),
+        }
+    }
+
+    /// Returns the names of the fields, separated by commas
+    ///
+    /// # Arguments
+    /// * `fields` - The fields, numbered row by row
+    fn field_names(&self, fields: &[usize]) -> String {
+        fields.iter().map(|&field| self.field_name(field)).collect::<Vec<_>>().join(", ")
+    }
+
+    /// Returns the numbers of a bitmask written after each other, like 358
+    ///
+    /// # Arguments
+    /// * `mask` - The numbers as a bitmask
+    fn numbers_name(mask: u64) -> String {
+        numbers_in(mask).map(|number| number.to_string()).collect::<Vec<_>>().join("")
+    }
+
+    /// Returns a step which removes candidates, or None if there is nothing to remove
+    ///
+    /// # Arguments
+    /// * `technique` - The technique that found the step
+    /// * `eliminations` - The candidates to remove, as (field, number)
+    /// * `reason` - Why the candidates can be removed, for the explanation
+    fn elimination_step(&self, technique: Technique, eliminations: Vec<(usize, usize)>, reason: String) -> Option<Step> {
+        if eliminations.is_empty() {
+            return None;
+        }
+        let removed = eliminations.iter()
+            .map(|&(field, number)| format!("{}<>{}", self.field_name(field), number))
+            .collect::<Vec<_>>()
+            .join(", ");
+        Some(Step {
+            technique,
+            placements: vec![],
+            eliminations: eliminations.iter().map(|&(field, number)| self.candidate(field, number)).collect(),
+            explanation: format!("{}: {}, so {}", technique, reason, removed),
+        })
+    }
+
+    /// Returns a step which fills in a number
+    ///
+    /// # Arguments
+    /// * `technique` - The technique that found the step
+    /// * `field` - The field to fill in
+    /// * `number` - The number that goes in the field
+    /// * `reason` - Why the number goes there, for the explanation
+    fn placement_step(&self, technique: Technique, field: usize, number: usize, reason: String) -> Step {
+        Step {
+            technique,
+            placements: vec![self.candidate(field, number)],
+            eliminations: vec![],
+            explanation: format!("{}: {}, so {}={}", technique, reason, self.field_name(field), number),
+        }
+    }
+
+    /// Returns the first step the technique can take, if any
+    ///
+    /// # Arguments
+    /// * `technique` - The technique to use
+    fn find_step(&self, technique: Technique) -> Option<Step> {
+        match technique {
+            Technique::HiddenSingle => self.find_hidden_single(),
+            Technique::NakedSingle => self.find_naked_single(),
+            Technique::Pointing => self.find_pointing(),
+            Technique::Claiming => self.find_claiming(),
+            Technique::NakedPair => self.find_naked_subset(2, technique),
+            Technique::NakedTriple => self.find_naked_subset(3, technique),
+            Technique::NakedQuad => self.find_naked_subset(4, technique),
+            Technique::HiddenPair => self.find_hidden_subset(2, technique),
+            Technique::HiddenTriple => self.find_hidden_subset(3, technique),
+            Technique::HiddenQuad => self.find_hidden_subset(4, technique),
+            Technique::XWing => self.find_fish(2, technique),
+            Technique::Swordfish => self.find_fish(3, technique),
+            Technique::Jellyfish => self.find_fish(4, technique),
+            Technique::XYWing => self.find_xy_wing(),
+            Technique::XYZWing => self.find_xyz_wing(),
+            Technique::WWing => self.find_w_wing(),
+            Technique::UniqueRectangle => self.find_unique_rectangle(),
+            Technique::SimpleColouring => self.find_simple_colouring(),
+            Technique::Chain => self.find_chain(),
+        }
+    }
+
+    /// Returns a step filling in a number that can only go in one place of a row, column or
+    /// subgrid, looking at the subgrids first
+    fn find_hidden_single(&self) -> Option<Step> {
+        // subgrids first, as those are the easiest to spot
+        let units = (2 * self.size..3 * self.size).chain(0..2 * self.size);
+        for unit in units {
+            for number in 1..=self.size {
+                let positions = self.positions(unit, number);
+                if positions.len() == 1 {
+                    let reason = format!("{} can only go in one place in {}", number, self.unit_name(unit));
+                    return Some(self.placement_step(Technique::HiddenSingle, positions[0], number, reason));
+                }
+            }
+        }
+        None
+    }
+
+    /// Returns a step filling in a field that has only one candidate left
+    fn find_naked_single(&self) -> Option<Step> {
+        let field = (0..self.size * self.size).find(|&field| self.candidates[field].count_ones() == 1)?;
+        let number = numbers_in(self.candidates[field]).next()?;
+        let reason = format!("{} is the only candidate left in {}", number, self.field_name(field));
+        Some(self.placement_step(Technique::NakedSingle, field, number, reason))
+    }
+
+    /// Returns a step for a number which can only go in one row or column of a subgrid, so it
+    /// can't go anywhere else in that row or column
+    fn find_pointing(&self) -> Option<Step> {
+        for subgrid in 2 * self.size..3 * self.size {
+            for number in 1..=self.size {
+                let positions = self.positions(subgrid, number);
+                if positions.len() < 2 {
+                    continue;
+                }
+
+                // the row or column (if any) that holds all positions
+                let first = positions[0];
+                let lines = [self.row_of(first), self.size + self.column_of(first)];
+                for line in lines {
+                    if !positions.iter().all(|&field| self.units_of(field).contains(&line)) {
+                        continue;
+                    }
+                    let eliminations = self.units[line].iter()
+                        .filter(|&&field| self.subgrid_of(field) + 2 * self.size != subgrid && self.has_candidate(field, number))
+                        .map(|&field| (field, number))
+                        .collect();
+                    let reason = format!("{} in {} can only go in {}", number, self.unit_name(subgrid), self.unit_name(line));
+                    if let Some(step) = self.elimination_step(Technique::Pointing, eliminations, reason) {
+                        return Some(step);
+                    }
+                }
+            }
+        }
+        None
+    }
+
+    /// Returns a step for a number which can only go in one subgrid within a row or column, so it
+    /// can't go anywhere else in that subgrid
+    fn find_claiming(&self) -> Option<Step> {
+        for line in 0..2 * self.size {
+            for number in 1..=self.size {
+                let positions = self.positions(line, number);
+                if positions.len() < 2 {
+                    continue;
+                }
+
+                let subgrid = 2 * self.size + self.subgrid_of(positions[0]);
+                if !positions.iter().all(|&field| self.units_of(field)[2] == subgrid) {
+                    continue;
+                }
+                let eliminations = self.units[subgrid].iter()
+                    .filter(|&&field| !self.units_of(field).contains(&line) && self.has_candidate(field, number))
+                    .map(|&field| (field, number))
+                    .collect();
+                let reason = format!("{} in {} can only go in {}", number, self.unit_name(line), self.unit_name(subgrid));
+                if let Some(step) = self.elimination_step(Technique::Claiming, eliminations, reason) {
+                    return Some(step);
+                }
+            }
+        }
+        None
+    }
+
+    /// Returns a step for `k` fields of a unit which hold only `k` candidates together, so those
+    /// numbers can't go anywhere else in the unit
+    ///
+    /// # Arguments
+    /// * `k` - The size of the subset, 2 for pairs, 3 for triples and 4 for quads
+    /// * `technique` - The technique to report the step with
+    fn find_naked_subset(&self, k: usize, technique: Technique) -> Option<Step> {
+        for unit in 0..self.units.len() {
+            let fields: Vec<usize> = self.units[unit].iter().copied()
+                .filter(|&field| (2..=k).contains(&(self.candidates[field].count_ones() as usize)))
+                .collect();
+
+            for subset in combinations(&fields, k) {
+                let mask = subset.iter().fold(0, |mask, &field| mask | self.candidates[field]);
+                if mask.count_ones() as usize != k {
+                    continue;
+                }
+                let eliminations = self.units[unit].iter()
+                    .filter(|field| !subset.contains(field))
+                    .flat_map(|&field| numbers_in(self.candidates[field] & mask).map(move |number| (field, number)))
+                    .collect();
+                let reason = format!("{} in {} only hold {}", self.field_names(&subset), self.unit_name(unit), Self::numbers_name(mask));
+                if let Some(step) = self.elimination_step(technique, eliminations, reason) {
+                    return Some(step);
+                }
+            }
+        }
+        None
+    }
+
+    /// Returns a step for `k` numbers which can only go in the same `k` fields of a unit, so those
+    /// fields can't hold any other number
+    ///
+    /// # Arguments
+    /// * `k` - The size of the subset, 2 for pairs, 3 for triples and 4 for quads
+    /// * `technique` - The technique to report the step with
+    fn find_hidden_subset(&self, k: usize, technique: Technique) -> Option<Step> {
+        for unit in 0..self.units.len() {
+            let numbers: Vec<usize> = (1..=self.size)
+                .filter(|&number| (2..=k).contains(&self.positions(unit, number).len()))
+                .collect();
+
+            for subset in combinations(&numbers, k) {
+                let mut fields: Vec<usize> = subset.iter().flat_map(|&number| self.positions(unit, number)).collect();
+                fields.sort_unstable();
+                fields.dedup();
+                if fields.len() != k {
+                    continue;
+                }
+                let mask = subset.iter().fold(0, |mask, &number| mask | bit(number));
+                let eliminations = fields.iter()
+                    .flat_map(|&field| numbers_in(self.candidates[field] & !mask).map(move |number| (field, number)))
+                    .collect();
+                let reason = format!("{} in {} can only go in {}", Self::numbers_name(mask), self.unit_name(unit), self.field_names(&fields));
+                if let Some(step) = self.elimination_step(technique, eliminations, reason) {
+                    return Some(step);
+                }
+            }
+        }
+        None
+    }
+
+    /// Returns a step for a number which can only go in `k` columns within `k` rows (or the other
+    /// way around), so it can't go anywhere else in those columns
+    ///
+    /// # Arguments
+    /// * `k` - The number of lines, 2 for an X-Wing, 3 for a Swordfish and 4 for a Jellyfish
+    /// * `technique` - The technique to report the step with
+    fn find_fish(&self, k: usize, technique: Technique) -> Option<Step> {
+        let size = self.size;
+        for number in 1..=size {
+            // first with rows as the base lines and columns as the cover lines, then the other way
+            for rows_are_base in [true, false] {
+                let field_at = |base: usize, cover: usize| if rows_are_base { base * size + cover } else { cover * size + base };
+                let cover_of = |field: usize| if rows_are_base { self.column_of(field) } else { self.row_of(field) };
+                let unit_offset = if rows_are_base { 0 } else { size };
+
+                // the base lines with 2 to k places for the number, and the cover lines of those
+                let mut base_lines = vec![];
+                let mut cover_masks = HashMap::new();
+                for base in 0..size {
+                    let positions = self.positions(unit_offset + base, number);
+                    if (2..=k).contains(&positions.len()) {
+                        base_lines.push(base);
+                        cover_masks.insert(base, positions.iter().fold(0u64, |mask, &field| mask | (1 << cover_of(field))));
+                    }
+                }
+
+                for subset in combinations(&base_lines, k) {
+                    let cover_mask = subset.iter().fold(0, |mask, base| mask | cover_masks[base]);
+                    if cover_mask.count_ones() as usize != k {
+                        continue;
+                    }
+                    let mut eliminations = vec![];
+                    for cover in (0..size).filter(|cover| cover_mask & (1 << cover) != 0) {
+                        for base in (0..size).filter(|base| !subset.contains(base)) {
+                            let field = field_at(base, cover);
+                            if self.has_candidate(field, number) {
+                                eliminations.push((field, number));
+                            }
+                        }
+                    }
+                    let base_names: Vec<String> = subset.iter().map(|base| self.unit_name(unit_offset + base)).collect();
+                    let reason = format!("{} in {} can only go in {} {}", number, base_names.join(", "), k,
+                                         if rows_are_base { "columns" } else { "rows" });
+                    if let Some(step) = self.elimination_step(technique, eliminations, reason) {
+                        return Some(step);
+                    }
+                }
+            }
+        }
+        None
+    }
+
+    /// Returns the fields which see every one of the given fields and still have the number as a
+    /// candidate, as (field, number)
+    ///
+    /// # Arguments
+    /// * `fields` - The fields that have to be seen
+    /// * `number` - The number to look for
+    fn fields_seeing_all(&self, fields: &[usize], number: usize) -> Vec<(usize, usize)> {
+        (0..self.size * self.size)
+            .filter(|&field| self.has_candidate(field, number) && fields.iter().all(|&other| self.sees(field, other)))
+            .map(|field| (field, number))
+            .collect()
+    }
+
+    /// Returns the fields with exactly two candidates
+    fn bivalue_fields(&self) -> Vec<usize> {
+        (0..self.size * self.size).filter(|&field| self.candidates[field].count_ones() == 2).collect()
+    }
+
+    /// Returns a step for a field with candidates xy which sees fields with xz and yz, so any
+    /// field seeing both of those can't be z
+    fn find_xy_wing(&self) -> Option<Step> {
+        for pivot in self.bivalue_fields() {
+            let pivot_mask = self.candidates[pivot];
+            for &pincer1 in &self.peers[pivot] {
+                let mask1 = self.candidates[pincer1];
+                if mask1.count_ones() != 2 || (mask1 & pivot_mask).count_ones() != 1 {
+                    continue;
+                }
+                // pivot = xy, pincer1 = xz, so pincer2 must be yz
+                let z = mask1 & !pivot_mask;
+                let mask2 = (pivot_mask & !mask1) | z;
+                for &pincer2 in &self.peers[pivot] {
+                    if self.candidates[pincer2] != mask2 {
+                        continue;
+                    }
+                    let number = numbers_in(z).next()?;
+                    let eliminations = self.fields_seeing_all(&[pincer1, pincer2], number);
+                    let reason = format!("{} ({}) sees {} ({}) and {} ({}), one of which must be {}",
+                                         self.field_name(pivot), Self::numbers_name(pivot_mask),
+                                         self.field_name(pincer1), Self::numbers_name(mask1),
+                                         self.field_name(pincer2), Self::numbers_name(mask2), number);
+                    if let Some(step) = self.elimination_step(Technique::XYWing, eliminations, reason) {
+                        return Some(step);
+                    }
+                }
+            }
+        }
+        None
+    }
+
+    /// Returns a step for a field with candidates xyz which sees fields with xz and yz, so any
+    /// field seeing all three can't be z
+    fn find_xyz_wing(&self) -> Option<Step> {
+        for pivot in 0..self.size * self.size {
+            let pivot_mask = self.candidates[pivot];
+            if pivot_mask.count_ones() != 3 {
+                continue;
+            }
+            let pincers: Vec<usize> = self.peers[pivot].iter().copied()
+                .filter(|&peer| self.candidates[peer].count_ones() == 2 && self.candidates[peer] & !pivot_mask == 0)
+                .collect();
+            for (i, &pincer1) in pincers.iter().enumerate() {
+                for &pincer2 in &pincers[i + 1..] {
+                    let (mask1, mask2) = (self.candidates[pincer1], self.candidates[pincer2]);
+                    if mask1 == mask2 || mask1 | mask2 != pivot_mask {
+                        continue;
+                    }
+                    let z = mask1 & mask2;
+                    let number = numbers_in(z).next()?;
+                    let eliminations = self.fields_seeing_all(&[pivot, pincer1, pincer2], number);
+                    let reason = format!("{} ({}) sees {} ({}) and {} ({}), one of which must be {}",
+                                         self.field_name(pivot), Self::numbers_name(pivot_mask),
+                                         self.field_name(pincer1), Self::numbers_name(mask1),
+                                         self.field_name(pincer2), Self::numbers_name(mask2), number);
+                    if let Some(step) = self.elimination_step(Technique::XYZWing, eliminations, reason) {
+                        return Some(step);
+                    }
+                }
+            }
+        }
+        None
+    }
+
+    /// Returns a step for two fields with the same two candidates xy, linked by a unit in which x
+    /// can only go in fields seeing one of them each, so any field seeing both can't be y
+    fn find_w_wing(&self) -> Option<Step> {
+        let bivalue_fields = self.bivalue_fields();
+        for (i, &field1) in bivalue_fields.iter().enumerate() {
+            for &field2 in &bivalue_fields[i + 1..] {
+                let mask = self.candidates[field1];
+                if self.candidates[field2] != mask || self.sees(field1, field2) {
+                    continue;
+                }
+                for link_number in numbers_in(mask) {
+                    let other_number = numbers_in(mask & !bit(link_number)).next()?;
+                    for unit in 0..self.units.len() {
+                        let positions = self.positions(unit, link_number);
+                        if positions.len() != 2 || positions.contains(&field1) || positions.contains(&field2) {
+                            continue;
+                        }
+                        let (end1, end2) = (positions[0], positions[1]);
+                        let connected = (self.sees(end1, field1) && self.sees(end2, field2))
+                            || (self.sees(end1, field2) && self.sees(end2, field1));
+                        if !connected {
+                            continue;
+                        }
+                        let eliminations = self.fields_seeing_all(&[field1, field2], other_number);
+                        let reason = format!("{} and {} ({}) are linked by the {} in {}, so one of them is {}",
+                                             self.field_name(field1), self.field_name(field2), Self::numbers_name(mask),
+                                             link_number, self.unit_name(unit), other_number);
+                        if let Some(step) = self.elimination_step(Technique::WWing, eliminations, reason) {
+                            return Some(step);
+                        }
+                    }
+                }
+            }
+        }
+        None
+    }
+
+    /// Returns a step for a rectangle over two subgrids of which three corners only hold the same
+    /// two candidates, so the fourth corner can't be either of them or the sudoku would have two
+    /// solutions
+    fn find_unique_rectangle(&self) -> Option<Step> {
+        let size = self.size;
+        for row1 in 0..size {
+            for row2 in row1 + 1..size {
+                for column1 in 0..size {
+                    for column2 in column1 + 1..size {
+                        let corners = [row1 * size + column1, row1 * size + column2, row2 * size + column1, row2 * size + column2];
+                        if corners.iter().any(|&field| self.values[field] != 0) {
+                            continue;
+                        }
+                        // the rectangle must lie in exactly two subgrids
+                        let mut subgrids: Vec<usize> = corners.iter().map(|&field| self.subgrid_of(field)).collect();
+                        subgrids.sort_unstable();
+                        subgrids.dedup();
+                        if subgrids.len() != 2 {
+                            continue;
+                        }
+
+                        for (i, &odd_corner) in corners.iter().enumerate() {
+                            let others: Vec<usize> = corners.iter().enumerate().filter(|&(j, _)| j != i).map(|(_, &field)| field).collect();
+                            let mask = self.candidates[others[0]];
+                            if mask.count_ones() != 2 || others.iter().any(|&field| self.candidates[field] != mask) {
+                                continue;
+                            }
+                            let eliminations = numbers_in(self.candidates[odd_corner] & mask)
+                                .map(|number| (odd_corner, number))
+                                .collect::<Vec<_>>();
+                            if eliminations.len() != 2 || self.candidates[odd_corner] == mask {
+                                continue;
+                            }
+                            let reason = format!("{} only hold {}, and {} can't as well or the sudoku would have two solutions",
+                                                 self.field_names(&others), Self::numbers_name(mask), self.field_name(odd_corner));
+                            if let Some(step) = self.elimination_step(Technique::UniqueRectangle, eliminations, reason) {
+                                return Some(step);
+                            }
+                        }
+                    }
+                }
+            }
+        }
+        None
+    }
+
+    /// Returns a step from colouring the conjugate pairs of a number, where two fields of the same
+    /// colour in one unit rule out that colour, and a field seeing both colours can't hold the
+    /// number
+    fn find_simple_colouring(&self) -> Option<Step> {
+        for number in 1..=self.size {
+            // the conjugate pairs: units in which the number can only go in two places
+            let mut links: HashMap<usize, Vec<usize>> = HashMap::new();
+            for unit in 0..self.units.len() {
+                let positions = self.positions(unit, number);
+                if positions.len() == 2 {
+                    links.entry(positions[0]).or_default().push(positions[1]);
+                    links.entry(positions[1]).or_default().push(positions[0]);
+                }
+            }
+
+            let mut colours: HashMap<usize, bool> = HashMap::new();
+            let mut starts: Vec<usize> = links.keys().copied().collect();
+            starts.sort_unstable();
+            for start in starts {
+                if colours.contains_key(&start) {
+                    continue;
+                }
+
+                // colour the connected fields, alternating between the two colours
+                let mut chain = vec![];
+                let mut queue = VecDeque::from([(start, true)]);
+                let mut consistent = true;
+                while let Some((field, colour)) = queue.pop_front() {
+                    match colours.get(&field) {
+                        Some(&existing) => consistent &= existing == colour,
+                        None => {
+                            colours.insert(field, colour);
+                            chain.push((field, colour));
+                            for &next in &links[&field] {
+                                queue.push_back((next, !colour));
+                            }
+                        }
+                    }
+                }
+                if !consistent {
+                    continue;
+                }
+                let with_colour = |colour: bool| chain.iter().filter(|&&(_, c)| c == colour).map(|&(field, _)| field).collect::<Vec<_>>();
+                let (on, off) = (with_colour(true), with_colour(false));
+
+                // colour wrap: two fields of the same colour see each other, so that colour is false
+                for fields in [&on, &off] {
+                    let wraps = fields.iter().any(|&a| fields.iter().any(|&b| self.sees(a, b)));
+                    if wraps {
+                        let eliminations = fields.iter().map(|&field| (field, number)).collect();
+                        let reason = format!("the chain of {} through {} has two fields of one colour in the same unit",
+                                             number, self.field_names(&chain.iter().map(|&(field, _)| field).collect::<Vec<_>>()));
+                        if let Some(step) = self.elimination_step(Technique::SimpleColouring, eliminations, reason) {
+                            return Some(step);
+                        }
+                    }
+                }
+
+                // colour trap: a field that sees both colours can't hold the number
+                let eliminations = (0..self.size * self.size)
+                    .filter(|&field| self.has_candidate(field, number) && !colours.contains_key(&field))
+                    .filter(|&field| on.iter().any(|&a| self.sees(field, a)) && off.iter().any(|&b| self.sees(field, b)))
+                    .map(|field| (field, number))
+                    .collect();
+                let reason = format!("either {} or {} hold {}", self.field_names(&on), self.field_names(&off), number);
+                if let Some(step) = self.elimination_step(Technique::SimpleColouring, eliminations, reason) {
+                    return Some(step);
+                }
+            }
+        }
+        None
+    }
+
+    /// Returns the candidates of which at least one must be true if the given candidate is false
+    ///
+    /// # Arguments
+    /// * `field` - The field of the candidate
+    /// * `number` - The number of the candidate
+    fn strong_links(&self, field: usize, number: usize) -> Vec<(usize, usize)> {
+        let mut links = vec![];
+        if self.candidates[field].count_ones() == 2 {
+            let other = numbers_in(self.candidates[field] & !bit(number)).next();
+            links.extend(other.map(|other| (field, other)));
+        }
+        for unit in self.units_of(field) {
+            let positions = self.positions(unit, number);
+            if positions.len() == 2 {
+                let other = if positions[0] == field { positions[1] } else { positions[0] };
+                if !links.contains(&(other, number)) {
+                    links.push((other, number));
+                }
+            }
+        }
+        links
+    }
+
+    /// Returns the candidates which can't be true at the same time as the given candidate
+    ///
+    /// # Arguments
+    /// * `field` - The field of the candidate
+    /// * `number` - The number of the candidate
+    fn weak_links(&self, field: usize, number: usize) -> Vec<(usize, usize)> {
+        let same_field = numbers_in(self.candidates[field] & !bit(number)).map(|other| (field, other));
+        let same_number = self.peers[field].iter()
+            .filter(|&&peer| self.has_candidate(peer, number))
+            .map(|&peer| (peer, number));
+        same_field.chain(same_number).collect()
+    }
+
+    /// Looks for alternating inference chains: starting from a candidate that is assumed false,
+    /// strong links make the next candidate true, and weak links make the one after that false.
+    /// When the chain makes a candidate true, either the start or that candidate is true, so
+    /// everything that conflicts with both can be removed. When it makes the start itself true,
+    /// the start must be the answer.
+    fn find_chain(&self) -> Option<Step> {
+        for start_field in 0..self.size * self.size {
+            for start_number in numbers_in(self.candidates[start_field]) {
+                let start = (start_field, start_number);
+
+                // breadth first, so the shortest chains are found first
+                let mut parents: HashMap<ChainNode, ChainNode> = HashMap::new();
+                let mut queue = VecDeque::from([(start, false)]);
+                parents.insert((start, false), (start, false));
+
+                while let Some((node, is_true)) = queue.pop_front() {
+                    if is_true {
+                        if let Some(step) = self.chain_conclusion(start, node, &parents) {
+                            return Some(step);
+                        }
+                    }
+                    let next_nodes = if is_true { self.weak_links(node.0, node.1) } else { self.strong_links(node.0, node.1) };
+                    for next in next_nodes {
+                        if let std::collections::hash_map::Entry::Vacant(entry) = parents.entry((next, !is_true)) {
+                            entry.insert((node, is_true));
+                            queue.push_back((next, !is_true));
+                        }
+                    }
+                }
+            }
+        }
+        None
+    }
+
+    /// Returns the step that follows from a chain which starts with `start` being false and ends
+    /// with `end` being true, if it gets us anywhere
+    ///
+    /// # Arguments
+    /// * `start` - The candidate the chain assumes to be false, as (field, number)
+    /// * `end` - The candidate the chain makes true, as (field, number)
+    /// * `parents` - For every node of the chain, the node it was reached from
+    fn chain_conclusion(&self, start: (usize, usize), end: (usize, usize),
+                        parents: &HashMap<ChainNode, ChainNode>) -> Option<Step> {
+        // write down the chain, with = for strong links and - for weak links
+        let mut chain = vec![(end, true)];
+        while chain.last() != Some(&(start, false)) {
+            let last = *chain.last()?;
+            chain.push(parents[&last]);
+        }
+        chain.reverse();
+        let chain_name: String = chain.iter().enumerate()
+            .map(|(i, &((field, number), is_true))| {
+                let link = if i == 0 { "" } else if is_true { "=" } else { "-" };
+                format!("{}({}){}", link, number, self.field_name(field))
+            })
+            .collect();
+
+        if end == start {
+            let reason = format!("assuming {}<>{} leads to {}={} through {}", self.field_name(start.0), start.1,
+                                 self.field_name(start.0), start.1, chain_name);
+            return Some(self.placement_step(Technique::Chain, start.0, start.1, reason));
+        }
+
+        let conflicts_with_end = self.weak_links(end.0, end.1);
+        let eliminations = self.weak_links(start.0, start.1).into_iter()
+            .filter(|candidate| *candidate != end && conflicts_with_end.contains(candidate))
+            .collect();
+        let reason = format!("in the chain {} either end is true", chain_name);
+        self.elimination_step(Technique::Chain, eliminations, reason)
+    }
+}
@@ -1,798 +1,606 @@
 /*
-    Sudoku_creator : This is a collection of functions which allows us to generate both Sudokus
-    that we can solve, as well as their solutions
+    Sudoku_creator : The command line interface to the sudoku_creator library, so that shell
+    scripts and batch jobs can generate, solve, grade, check and transform sudokus. All of the work
+    is done by the library, this file only reads the arguments and writes the results.
 
-    Right now, it can only generate standard sudokus of size 9x9. A sudoku is possible, as long as
-    width = height and square_root(width) = postivie integer. Meaning, you can have sudokus
-    of size 1x1, 4x4, 9x9, 16x16, 25x25, 36x36 and so on.
+    Sudokus are read one per line, written row by row as in `parse_sudoku` (so
+    ".4....2..3....4." for a 4x4), from a file or from the standard input. Empty lines, lines
+    starting with # and anything after the first word of a line are skipped, so the output of
+    `generate` can be read back in. Run `sudoku_creator help` for all commands and options.
+
+    The exit code is 0 when everything went fine, 1 when some sudoku couldn't be generated,
+    solved, graded or read, 2 when the arguments are wrong and 3 when a file can't be read.
 
     Author : Martijn Folmer
     Date : 19-01-2024
  */
 
-use rand::seq::SliceRandom;     // random slices
-use rand::Rng;                  // random numbers
-use std::collections::HashSet;  // Get a hashset (which is an unordered list of unique values)
-use num::integer::sqrt;         // square root
-
-
-
-/// Fill in a row with values, to create a filled in sudoku
-///
-/// # Arguments
-/// `sudoku` - A 2D vector (`Vec<Vec<i32>>`) representing the Sudoku grid.
-/// * 'numbers' - the numbers to fill in
-/// 'row_index' - The row to fill in
-/// 'column_offset' - the offset to start filling in values. So if column_offset = 3, we start filling in the values at column_idx = 3
-fn fill_row(mut sudoku : Vec<Vec<i32>>, numbers:&Vec<i32>, row_index:usize, column_offset:usize) -> Vec<Vec<i32>>{
-
-    for (i, &n) in numbers.iter().enumerate(){
-        let mut idx = i + column_offset;
-        if idx>=9{
-            idx -= 9;
-        }
-        sudoku[row_index][idx] = n;
-    }
-
-    sudoku
-}
-
-/// Get a random number within a range
-///
-/// # Arguments
-/// 'range' - the range from within the random number is selected
-fn generate_random_number(range: std::ops::Range<i32>) -> i32{
-    // will generate 1 number inside of the range given
-    let mut rng = rand::thread_rng();
-
-    // Generate the first random number
-    let num = rng.gen_range(range);
-
-    num
+use std::fmt;
+use std::io::{self, BufRead, BufReader};
+use std::process::ExitCode;
+use std::time::Instant;
+
+use sudoku_creator::{
+    canonical_form, format_sudoku, format_sudoku_line, generate_full_sudoku_with_box_shape, generate_puzzle_with,
+    generate_sudoku_to_solve_with, get_solution, get_solution_with, grade_sudoku, has_unique_solution, parse_sudoku,
+    random_seed, seeded_rng, BoxShape, Difficulty, Digit, GenerationBudget, GenerationFailure, GenerationTarget, Grid,
+    Puzzle, Reflection, RemovalMode, RemovalOptions, SolveError, SolverBackend, Symmetry, Transform, MAX_CANONICAL_SIZE,
+};
+
+
+const USAGE: &str = "\
+Usage: sudoku_creator <command> [options]
+
+Commands:
+  generate [options]            Generate sudokus to solve
+      --size <width>            The width of the sudoku (default 9)
+      --box <rows>x<columns>    The shape of the subgrids, instead of the standard one for the size
+      --count <number>          How many sudokus to generate (default 1)
+      --difficulty <level>      easy, medium, hard, expert or diabolical
+      --symmetry <symmetry>     none, half-turn, quarter-turn, dihedral, left-right, top-bottom,
+                                main-diagonal or anti-diagonal
+      --empty <number>          How many fields to empty (default: as many as possible)
+      --seed <number>           The seed of the first sudoku, the next ones count up from it
+      --attempts <number>       How many sudokus to try to hit the difficulty (default 200)
+      --solution                Also write the solution
+  solve [file] [options]        Solve every sudoku
+      --backend <backend>       auto, bitmask, backtracking or dlx (default auto: bitmask below
+                                16x16, dlx from there on)
+  grade [file] [options]        Tell how hard every sudoku is
+  validate [file] [options]     Check that every sudoku has exactly one solution
+  canonicalize [file] [options] Write every sudoku in its canonical form (up to 12x12)
+  transform [file] [options]    Transform every sudoku, in the order of the options below
+      --transpose               Swap the rows and the columns
+      --rotate <quarter turns>  Turn clockwise, by 1, 2 or 3 quarter turns
+      --reflect <line>          Mirror in left-right, top-bottom, main-diagonal or anti-diagonal
+      --relabel <numbers>       Replace the numbers 1, 2, 3... with these, like 987654321
+      --seed <number>           Apply random transforms drawn from this seed
+  help                          Show this text
+
+Options for every command that reads sudokus:
+      --box <rows>x<columns>    The shape of the subgrids of the sudokus that are read
+
+Options for every command that writes sudokus (generate, solve, canonicalize and transform):
+      --format <format>         line (one sudoku per line, the default) or grid
+
+Without a file, or with -, the sudokus are read from the standard input.
+
+Exit codes: 0 when everything went fine, 1 when some sudoku failed, 2 for wrong arguments and 3
+when a file can't be read.
+";
+
+/// The exit code when some sudoku couldn't be generated, solved, graded or read
+const EXIT_FAILED: u8 = 1;
+/// The exit code when the arguments are wrong
+const EXIT_USAGE: u8 = 2;
+/// The exit code when a file can't be read
+const EXIT_IO: u8 = 3;
+
+/// The reasons why a command can't run at all
+enum CliError {
+    /// The arguments are wrong
+    Usage(String),
+    /// The input can't be read
+    Io(String),
 }
 
-///Return two random numbers which are not the same as eachother, within a range
-///
-/// # Arguments
-/// 'range' - the range from within the two numbers are selected
-fn generate_two_unique_random_numbers(range: std::ops::Range<i32>) -> (usize, usize) {
-    // will generate 2 numbers inside of range.
-    // if range = 0..3, the numbers can be [0, 1, 2], so the possible returns are [0, 1], [0, 2], [1, 0], [1,2], [2, 0], [2, 1]
-    let mut rng = rand::thread_rng();
-
-    // Generate the first random number
-    let num1 = rng.gen_range(range.clone());
-
-    // Generate the second random number until it is different from the first
-    let mut num2;
-    loop {
-        num2 = rng.gen_range(range.clone());
-        if num2 != num1 {
-            break;
+impl fmt::Display for CliError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            CliError::Usage(message) => write!(f, "{}\nRun `sudoku_creator help` for the commands and options.", message),
+            CliError::Io(message) => write!(f, "{}", message),
         }
     }
-
-    (num1.try_into().unwrap(), num2.try_into().unwrap())
 }
 
-
-/// Flip the values between 2 rows
-///
-/// # Arguments
-/// `sudoku` - A 2D vector (`Vec<Vec<i32>>`) representing the Sudoku grid.
-/// 'row_idx1' - The index of one of the row to swap
-/// 'row_idx2' - The index of the other of the row to swap
-fn flip_row(mut sudoku: Vec<Vec<i32>>, row_idx1: usize, row_idx2: usize) -> Vec<Vec<i32>> {
-    // Swap the rows directly without using temporary vectors
-    for i in 0..sudoku[row_idx1].len() {
-        let temp = sudoku[row_idx1][i];
-        sudoku[row_idx1][i] = sudoku[row_idx2][i];
-        sudoku[row_idx2][i] = temp;
-    }
-
-    // Return the sudoku
-    sudoku
+/// How sudokus are written
+#[derive(Clone, Copy, PartialEq, Eq)]
+enum Format {
+    /// One sudoku per line
+    Line,
+    /// Row by row, as `format_sudoku` does
+    Grid,
 }
 
-/// Randomly flipping the rows within a specific subgrid, to further randomize the sudoku
-///
-/// # Arguments
-/// * `sudoku` - A reference to a 2D vector (`&Vec<Vec<i32>>`) representing the Sudoku grid.
-/// 'number_of_attempts' - how many times to attempt to flip any two rows
-/// 'row_lower_idx' - the upper most value of the rows of the subgrids
-/// 'row_upper_idx' - the lower most value of the rows of the subgrids
-fn flip_rows(mut sudoku: Vec<Vec<i32>>, number_of_attempts:i32, row_lower_idx:i32, row_upper_idx:i32) -> Vec<Vec<i32>>{
-    // will randomly flip the rows between an upper and lower idx
-    for _ in 0..number_of_attempts{
-        let (random_num1, random_num2) = generate_two_unique_random_numbers(row_lower_idx..(row_upper_idx+1));
-        sudoku = flip_row(sudoku, random_num1, random_num2);
-    }
-
-    //return the sudoku
-    sudoku
-
-}
-/// Randomly flipping the rows within the subgrids, to further randomize the sudoku
-///
-/// # Arguments
-/// * `sudoku` - A reference to a 2D vector (`&Vec<Vec<i32>>`) representing the Sudoku grid.
-fn flip_all_rows(mut sudoku: Vec<Vec<i32>>) -> Vec<Vec<i32>>{
-    // flip all of the sets of rows
-    sudoku = flip_rows(sudoku, 5, 0, 2);
-    sudoku = flip_rows(sudoku, 5, 3, 5);
-    sudoku = flip_rows(sudoku, 5, 6, 8);
-
-    sudoku
+/// The arguments after the command: options with a value, options without one, and the rest
+struct Arguments {
+    values: Vec<(String, String)>,
+    flags: Vec<String>,
+    positional: Vec<String>,
 }
 
-/// Randomly flipping the rows within the subgrids, to further randomize the sudoku
-///
-/// # Arguments
-/// * `sudoku` - A reference to a 2D vector (`&Vec<Vec<i32>>`) representing the Sudoku grid.
-fn flip_grid_rows(mut sudoku: Vec<Vec<i32>>) -> Vec<Vec<i32>>{
-
-    let rows_to_swap1 = vec![0, 1, 2];
-    let rows_to_swap2 = vec![3, 4, 5];
-    let rows_to_swap3 = vec![6, 7, 8];
-
-    let vector_of_vectors: Vec<Vec<usize>> = vec![rows_to_swap1, rows_to_swap2, rows_to_swap3];
-
-    for _ in 0..5{
-        let (random_num1, random_num2) = generate_two_unique_random_numbers(0..3);
-        for i in 0..3{
-            sudoku = flip_row(sudoku, vector_of_vectors[random_num1][i], vector_of_vectors[random_num2][i]);
+impl Arguments {
+    /// Splits the arguments into options and the rest. Options with a value are written as
+    /// `--name value` or `--name=value`.
+    ///
+    /// # Arguments
+    /// * `arguments` - The arguments after the command
+    /// * `with_value` - The names of the options that take a value
+    /// * `flags` - The names of the options that don't take a value
+    fn parse(arguments: &[String], with_value: &[&str], flags: &[&str]) -> Result<Arguments, CliError> {
+        let mut parsed = Arguments { values: vec![], flags: vec![], positional: vec![] };
+        let mut arguments = arguments.iter();
+        while let Some(argument) = arguments.next() {
+            let Some(option) = argument.strip_prefix("--") else {
+                parsed.positional.push(argument.clone());
+                continue;
+            };
+            let (name, value) = match option.split_once('=') {
+                Some((name, value)) => (name, Some(value.to_string())),
+                None => (option, None),
+            };
+
+            if with_value.contains(&name) {
+                let value = value.or_else(|| arguments.next().cloned())
+                    .ok_or_else(|| CliError::Usage(format!("--{} needs a value", name)))?;
+                parsed.values.push((name.to_string(), value));
+            } else if flags.contains(&name) && value.is_none() {
+                parsed.flags.push(name.to_string());
+            } else {
+                return Err(CliError::Usage(format!("unknown option {}", argument)));
+            }
         }
+        Ok(parsed)
     }
 
-    //return the sudoku
-    sudoku
-}
-
-
-///Flip the values between 2 columns
-///
-/// # Arguments
-/// `sudoku` - A 2D vector (`Vec<Vec<i32>>`) representing the Sudoku grid.
-/// 'column_idx1' - The index of one of the columns to swap
-/// 'column_idx2' - The index of the other of the columns to swap
-fn flip_column(mut sudoku: Vec<Vec<i32>>, column_idx1: usize, column_idx2:usize) -> Vec<Vec<i32>>{
-    // swap the columns directly without using temporary vectors
-    for i in 0..sudoku.len(){
-        let temp = sudoku[i][column_idx1];
-        sudoku[i][column_idx1] = sudoku[i][column_idx2];
-        sudoku[i][column_idx2] = temp;
+    /// Returns the value of an option, read with the given function, or None if it wasn't given
+    ///
+    /// # Arguments
+    /// * `name` - The name of the option, without the dashes
+    /// * `read` - Reads the value, or returns None if it isn't valid
+    /// * `expected` - What a valid value looks like, for the error message
+    fn get<T>(&self, name: &str, read: impl Fn(&str) -> Option<T>, expected: &str) -> Result<Option<T>, CliError> {
+        match self.values.iter().rev().find(|(option, _)| option == name) {
+            None => Ok(None),
+            Some((_, value)) => read(value).map(Some)
+                .ok_or_else(|| CliError::Usage(format!("--{} should be {}, got {:?}", name, expected, value))),
+        }
     }
 
-    //Return the sudoku
-    sudoku
-}
-
-/// Randomly flipping the columns within a specific subgrid, to further randomize the sudoku
-///
-/// # Arguments
-/// * `sudoku` - A reference to a 2D vector (`&Vec<Vec<i32>>`) representing the Sudoku grid.
-/// 'number_of_attempts' - how many times to attempt to flip any two columns
-/// 'column_lower_idx' - the left most value of the columns of the subgrids
-/// 'column_upper_idx' - the right most value of the columns of the subgrids
-fn flip_columns(mut sudoku: Vec<Vec<i32>>, number_of_attempts:i32, column_lower_idx:i32, column_upper_idx:i32) -> Vec<Vec<i32>>{
-    // will randomly flip the columns between and upper and lower idx
-    for _ in 0..number_of_attempts{
-        let (random_num1, random_num2) = generate_two_unique_random_numbers(column_lower_idx..(column_upper_idx+1));
-        sudoku = flip_column(sudoku, random_num1, random_num2);
+    /// Returns a whole number option, or None if it wasn't given
+    ///
+    /// # Arguments
+    /// * `name` - The name of the option, without the dashes
+    fn number<T: std::str::FromStr>(&self, name: &str) -> Result<Option<T>, CliError> {
+        self.get(name, |value| value.parse().ok(), "a whole number")
     }
 
-    //return the sudoku
-    sudoku
-}
-
-
-/// Randomly flipping the columns within the subgrids, to further randomize the sudoku
-///
-/// # Arguments
-/// * `sudoku` - A reference to a 2D vector (`&Vec<Vec<i32>>`) representing the Sudoku grid.
-fn flip_all_columns(mut sudoku: Vec<Vec<i32>>) -> Vec<Vec<i32>>{
-    // flip all of the sets of rows
-
-    sudoku = flip_columns(sudoku, 5, 0, 2);
-    sudoku = flip_columns(sudoku, 5, 3, 5);
-    sudoku = flip_columns(sudoku, 5, 6, 8);
-
-    // return the sudoku
-    sudoku
-}
-
-/// Randomly flipping the columns within the subgrids, to further randomize the sudoku
-///
-/// # Arguments
-/// * `sudoku` - A reference to a 2D vector (`&Vec<Vec<i32>>`) representing the Sudoku grid.
-fn flip_grid_columns(mut sudoku: Vec<Vec<i32>>) -> Vec<Vec<i32>>{
-
-    let columns_to_swap1 = vec![0, 1, 2];
-    let columns_to_swap2 = vec![3, 4, 5];
-    let columns_to_swap3 = vec![6, 7, 8];
-
-    let vector_of_vectors: Vec<Vec<usize>> = vec![columns_to_swap1, columns_to_swap2, columns_to_swap3];
-
-    for _ in 0..5{
-        let (random_num1, random_num2) = generate_two_unique_random_numbers(0..3);
-        for i in 0..3{
-            sudoku = flip_column(sudoku, vector_of_vectors[random_num1][i], vector_of_vectors[random_num2][i]);
-        }
+    /// Whether an option without a value was given
+    ///
+    /// # Arguments
+    /// * `name` - The name of the option, without the dashes
+    fn flag(&self, name: &str) -> bool {
+        self.flags.iter().any(|flag| flag == name)
     }
 
-    //return the sudoku
-    sudoku
-}
-
-/// Rotate the sudoku by 90 degrees
-///
-/// # Arguments
-/// * `sudoku` - A reference to a 2D vector (`&Vec<Vec<i32>>`) representing the Sudoku grid.
-fn rotate_90_degrees(sudoku: &mut Vec<Vec<i32>>) {
-    let n = sudoku.len();
-    let m = sudoku[0].len();
-    let mut rotated_sudoku = vec![vec![0; n]; m];
-
-    for i in 0..n {
-        for j in 0..m {
-            rotated_sudoku[j][n - 1 - i] = sudoku[i][j];
-        }
+    /// The output format that was asked for
+    fn format(&self) -> Result<Format, CliError> {
+        let read = |value: &str| match value {
+            "line" => Some(Format::Line),
+            "grid" => Some(Format::Grid),
+            _ => None,
+        };
+        Ok(self.get("format", read, "line or grid")?.unwrap_or(Format::Line))
     }
 
-    *sudoku = rotated_sudoku;
+    /// The shape of the subgrids that was asked for, as `--box 2x3`
+    fn box_shape(&self) -> Result<Option<BoxShape>, CliError> {
+        let read = |value: &str| {
+            let (rows, columns) = value.split_once('x')?;
+            let (rows, columns) = (rows.parse().ok()?, columns.parse().ok()?);
+            (rows > 0 && columns > 0).then(|| BoxShape::new(rows, columns))
+        };
+        self.get("box", read, "the rows and columns of a subgrid, like 3x3")
+    }
 }
 
-/// Rotate the sudoku by 180 degrees
-///
-/// # Arguments
-/// * `sudoku` - A reference to a 2D vector (`&Vec<Vec<i32>>`) representing the Sudoku grid.
-fn rotate_180_degrees(sudoku: &mut Vec<Vec<i32>>) {
-    let n = sudoku.len();
-    let m = sudoku[0].len();
-    let mut rotated_sudoku = vec![vec![0; n]; m];
-
-    for i in 0..n {
-        for j in 0..m {
-            rotated_sudoku[n - 1 - i][m - 1 - j] = sudoku[i][j];
+/// The options every command that reads sudokus takes
+const READ_OPTIONS: [&str; 1] = ["box"];
+
+fn main() -> ExitCode {
+    let arguments: Vec<String> = std::env::args().skip(1).collect();
+    let Some(command) = arguments.first() else {
+        eprint!("{}", USAGE);
+        return ExitCode::from(EXIT_USAGE);
+    };
+
+    let rest = &arguments[1..];
+    let result = match command.as_str() {
+        "generate" => generate(rest),
+        "solve" => solve(rest),
+        "grade" => grade(rest),
+        "validate" => validate(rest),
+        "canonicalize" => canonicalize(rest),
+        "transform" => transform(rest),
+        "help" | "--help" | "-h" => {
+            print!("{}", USAGE);
+            Ok(true)
+        }
+        _ => Err(CliError::Usage(format!("unknown command {:?}", command))),
+    };
+
+    match result {
+        Ok(true) => ExitCode::SUCCESS,
+        Ok(false) => ExitCode::from(EXIT_FAILED),
+        Err(error) => {
+            eprintln!("sudoku_creator: {}", error);
+            ExitCode::from(match error {
+                CliError::Usage(_) => EXIT_USAGE,
+                CliError::Io(_) => EXIT_IO,
+            })
         }
     }
-
-    *sudoku = rotated_sudoku;
 }
 
-/// Rotate the sudoku by 270 degrees
+/// Writes a sudoku to the standard output
 ///
 /// # Arguments
-/// * `sudoku` - A reference to a 2D vector (`&Vec<Vec<i32>>`) representing the Sudoku grid.
-fn rotate_270_degrees(sudoku: &mut Vec<Vec<i32>>) {
-    let n = sudoku.len();
-    let m = sudoku[0].len();
-    let mut rotated_sudoku = vec![vec![0; n]; m];
-
-    for i in 0..n {
-        for j in 0..m {
-            rotated_sudoku[m - 1 - j][i] = sudoku[i][j];
-        }
+/// * `sudoku` - A reference to the Sudoku grid.
+/// * `format` - How to write it
+fn write_sudoku(sudoku: &Grid, format: Format) {
+    match format {
+        Format::Line => println!("{}", format_sudoku_line(sudoku)),
+        Format::Grid => println!("{}", format_sudoku(sudoku)),
     }
-
-    *sudoku = rotated_sudoku;
 }
 
-/// Randomly rotates the sudoku by either 0, 90, 180 or 270 degrees
+/// Reads the sudokus from the file in the arguments, or from the standard input, and calls the
+/// action for every sudoku that can be read. Sudokus that can't be read are reported. Returns
+/// True if every sudoku could be read and the action returned True for all of them.
 ///
 /// # Arguments
-/// * `sudoku` - A reference to a 2D vector (`&Vec<Vec<i32>>`) representing the Sudoku grid.
-fn random_rotate(sudoku: &mut Vec<Vec<i32>>){
-    // rotate the sudoku by 0, 90, 180 or 270 degrees.
-    let rot_num = generate_random_number(0..3);
-
-    if rot_num ==1 {
-        rotate_90_degrees(sudoku);
+/// * `arguments` - The parsed arguments of the command
+/// * `action` - What to do with every sudoku, gets the line number for error messages
+fn for_each_sudoku(arguments: &Arguments, mut action: impl FnMut(usize, Grid) -> bool) -> Result<bool, CliError> {
+    if arguments.positional.len() > 1 {
+        return Err(CliError::Usage(format!("expected at most one file, got {}", arguments.positional.join(" "))));
     }
-    else if rot_num == 2{
-        rotate_180_degrees(sudoku);
-    }
-    else if rot_num == 3 {
-        rotate_270_degrees(sudoku);
+    let box_shape = arguments.box_shape()?;
+    let path = arguments.positional.first().filter(|path| *path != "-");
+    let reader: Box<dyn BufRead> = match path {
+        None => Box::new(BufReader::new(io::stdin())),
+        Some(path) => {
+            let file = std::fs::File::open(path).map_err(|error| CliError::Io(format!("can't open {}: {}", path, error)))?;
+            Box::new(BufReader::new(file))
+        }
+    };
+    let source = path.map_or("<stdin>", |path| path.as_str());
+
+    let mut all_fine = true;
+    for (index, line) in reader.lines().enumerate() {
+        let line = line.map_err(|error| CliError::Io(format!("can't read {}: {}", source, error)))?;
+        let Some(word) = line.split_whitespace().next().filter(|word| !word.starts_with('#')) else {
+            continue;
+        };
+        match parse_sudoku(word, box_shape) {
+            Ok(sudoku) => all_fine &= action(index + 1, sudoku),
+            Err(error) => {
+                eprintln!("line {}: {}", index + 1, error);
+                all_fine = false;
+            }
+        }
     }
+    Ok(all_fine)
 }
 
-/// Returns a vector with all values from a particular column in the sudoku
+/// The `generate` command
 ///
 /// # Arguments
-/// * `sudoku` - A reference to a 2D vector (`&Vec<Vec<i32>>`) representing the Sudoku grid.
-/// 'column_idx' - Column index
-fn get_column(sudoku: &Vec<Vec<i32>>, column_idx : i32) -> Vec<i32>{
-
-    let mut column = Vec::new();
-    let n = sudoku.len();
-
-    for i in 0..n {
-        column.push(sudoku[i][column_idx as usize]);
+/// * `arguments` - The arguments after the command
+fn generate(arguments: &[String]) -> Result<bool, CliError> {
+    let with_value = ["size", "box", "count", "difficulty", "symmetry", "empty", "seed", "attempts", "format"];
+    let arguments = Arguments::parse(arguments, &with_value, &["solution"])?;
+    if let Some(argument) = arguments.positional.first() {
+        return Err(CliError::Usage(format!("generate takes no files, got {:?}", argument)));
     }
-    column
-}
-
-/// Returns a vector with all values from a particular row in the sudoku
-///
-/// # Arguments
-/// * `sudoku` - A reference to a 2D vector (`&Vec<Vec<i32>>`) representing the Sudoku grid.
-/// 'row_idx' - Row index
-fn get_row(sudoku: &Vec<Vec<i32>>, row_idx : i32) -> Vec<i32>{
-
-    let mut row = Vec::new();
-    let n = sudoku.len();
 
-    for i in 0..n {
-        row.push(sudoku[row_idx as usize][i]);
+    let size = arguments.number("size")?.unwrap_or(9);
+    let box_shape = match arguments.box_shape()? {
+        Some(box_shape) => box_shape,
+        None => BoxShape::for_size(size)
+            .ok_or_else(|| CliError::Usage(format!("there is no standard shape of subgrids for a {}x{} sudoku, use --box", size, size)))?,
+    };
+    if box_shape.size() > 35 {
+        return Err(CliError::Usage("sudokus of up to 35x35 can be generated".to_string()));
     }
-    row
-}
-
-
-/// Returns a vector with all values from a particular subgrid in the sudoku
-///
-/// # Arguments
-/// * `sudoku` - A reference to a 2D vector (`&Vec<Vec<i32>>`) representing the Sudoku grid.
-/// 'row_idx1' - Top left row index
-/// 'row_idx2' - Bottom right row index
-/// 'column_idx1' - Top left column index
-/// 'column_idx2' - Bottom right column index
-fn get_subgrid(sudoku: &Vec<Vec<i32>>, row_idx1 : i32, row_idx2 : i32, column_idx1 : i32, column_idx2 : i32) -> Vec<i32> {
-    let mut subgrid = Vec::new();
-
-    for i in row_idx1..row_idx2{
-        for j in column_idx1..column_idx2 {
-            subgrid.push(sudoku[i as usize][j as usize]);
+    let count: u64 = arguments.number("count")?.unwrap_or(1);
+    let difficulty = arguments.get("difficulty", read_difficulty, "easy, medium, hard, expert or diabolical")?;
+    let symmetry = arguments.get("symmetry", read_symmetry, "a symmetry like half-turn")?.unwrap_or_default();
+    let empty: Option<usize> = arguments.number("empty")?;
+    let seed = arguments.number("seed")?.unwrap_or_else(random_seed);
+    let budget = GenerationBudget { max_attempts: arguments.number("attempts")?.unwrap_or(200), max_duration: None };
+    let format = arguments.format()?;
+
+    let mut all_fine = true;
+    for index in 0..count {
+        let seed = seed.wrapping_add(index);
+        let puzzle = match (difficulty, symmetry, empty) {
+            // the graded generator is the quickest way to a difficulty
+            (Some(difficulty), Symmetry::None, None) => generate_puzzle_with(difficulty, box_shape, budget, seed),
+            _ => generate_with_options(box_shape, difficulty, symmetry, empty, budget, seed),
+        };
+        match puzzle {
+            Ok(puzzle) => write_puzzle(&puzzle, format, arguments.flag("solution")),
+            Err(failure) => {
+                eprintln!("{}", failure);
+                all_fine = false;
+            }
         }
     }
-    subgrid
+    Ok(all_fine)
 }
 
-/// Returns True if there are no duplicates in the given Vec<i32>
+/// Generates a sudoku with a symmetry or a number of empty fields. When a difficulty is asked for,
+/// new sudokus are generated until one has that difficulty, or the budget runs out.
 ///
 /// # Arguments
-/// * 'vec' - a reference to the Vec<i32> that we are checking for duplicates
-fn is_vec_valid(vec: &Vec<i32>) -> bool {
-
-    // A hash set = unordered set of unique elements, it does not allow duplicates. When we insert
-    // and get false, it means the value is already inside of the hashmap.
-    let mut seen = std::collections::HashSet::new();
-
-    for &num in vec {
-        if num != 0 {
-            if !seen.insert(num) {
-                // The number is already in the HashSet, meaning it's a duplicate non-zero number
-                return false;
+/// * `box_shape` - The shape of the subgrids of the sudoku
+/// * `difficulty` - How hard the sudoku should be, or None for any difficulty
+/// * `symmetry` - The symmetry of the given numbers
+/// * `empty` - How many fields to empty, or None for as many as possible
+/// * `budget` - How many sudokus may be tried
+/// * `seed` - The seed for the random numbers
+fn generate_with_options(box_shape: BoxShape, difficulty: Option<Difficulty>, symmetry: Symmetry, empty: Option<usize>,
+                         budget: GenerationBudget, seed: u64) -> Result<Puzzle, GenerationFailure> {
+    let start = Instant::now();
+    let mut rng = seeded_rng(seed);
+    let mode = if empty.is_some() { RemovalMode::Unique } else { RemovalMode::Minimal };
+    let options = RemovalOptions { mode, symmetry, ..RemovalOptions::default() };
+
+    let mut attempts = 0;
+    while attempts < budget.max_attempts.max(1) {
+        attempts += 1;
+        let solution = generate_full_sudoku_with_box_shape(box_shape, &mut rng);
+        let report = generate_sudoku_to_solve_with(&solution, empty.unwrap_or(0), &options, &mut rng);
+        let grade = grade_sudoku(&report.sudoku).expect("sudokus of up to 35x35 can be graded");
+        if difficulty.is_none_or(|difficulty| grade.difficulty == difficulty) {
+            if !report.target_reached() {
+                eprintln!("only {} fields could be emptied (seed {})", report.removed, seed);
             }
+            return Ok(Puzzle { sudoku: report.sudoku, solution, grade, seed });
         }
     }
-    true
-}
-
-/// Returns True/False, based on whether all rules are followed for a specific spot defined by
-/// (xco, yco). The rules are no duplicates in rows, columns or within the subgrid
-///
-///  # Arguments
-/// * `sudoku` - A reference to a 2D vector (`&Vec<Vec<i32>>`) representing the Sudoku grid.
-/// 'xco' - the column coordinate of the spot we want to check
-/// 'yco' - the row coordinate of the spot we want to check
-fn is_loc_valid(sudoku: &Vec<Vec<i32>>, xco : i32, yco:i32) -> bool {
-
-    // Check if there are any errors for this grid space (meaning if there are any non-zero
-    // duplicates)
-
-    // get the values of the row and column
-    let row = get_row(&sudoku, yco);
-    let column = get_column(&sudoku, xco);
-
-    // get the values of the subgrid
-    let coor = get_subgrid_coor(xco, yco);
-    let subgrid = get_subgrid(&sudoku, coor.0, coor.2, coor.1, coor.3);
-
-    // return if the row, column and subgrid are all valid (so no non-zero duplicates)
-    is_vec_valid(&row) && is_vec_valid(&column) && is_vec_valid(&subgrid)
 
+    // without a difficulty the first attempt is always returned, so we only get here with one
+    let target = GenerationTarget::Difficulty(difficulty.unwrap_or(Difficulty::Easy));
+    Err(GenerationFailure { target, attempts, elapsed: start.elapsed(), closest: None, seed })
 }
 
-/// Given a vector, return another vector with all numbers between 1 and 9 which are not present
-/// in the original vector
+/// Writes a generated sudoku together with its seed, and in grid format also its difficulty
 ///
 /// # Arguments
-/// * 'vec' - A reference to a Vec<i32>, which contains any i32 numbers
-fn find_missing_numbers(vec: &Vec<i32>) -> Vec<i32> {
-    let mut present_numbers = vec![];
-
-    // Collect the unique non-zero numbers in the vector
-    for &num in vec {
-        if num != 0 {
-            present_numbers.push(num);
+/// * `puzzle` - The generated sudoku
+/// * `format` - How to write it
+/// * `with_solution` - Whether to write the solution as well
+fn write_puzzle(puzzle: &Puzzle, format: Format, with_solution: bool) {
+    match format {
+        Format::Line if with_solution =>
+            println!("{} {} # seed {}", format_sudoku_line(&puzzle.sudoku), format_sudoku_line(&puzzle.solution), puzzle.seed),
+        Format::Line => println!("{} # seed {}", format_sudoku_line(&puzzle.sudoku), puzzle.seed),
+        Format::Grid => {
+            println!("To Solve Sudoku");
+            write_sudoku(&puzzle.sudoku, format);
+            if with_solution {
+                println!("Filled sudoku");
+                write_sudoku(&puzzle.solution, format);
+            }
+            println!("Difficulty: {}", puzzle.grade);
+            println!("Seed: {}", puzzle.seed);
+            println!();
         }
     }
-
-    // Create a HashSet from the collected numbers (so all unique values)
-    let present_set: std::collections::HashSet<_> = present_numbers.iter().cloned().collect();
-
-    // Find the missing numbers between 1 and 9
-    (1..=9)
-        .filter(|&num| !present_set.contains(&num))
-        .collect()
 }
 
-
-///Given three vectors, return all numbers which all three have in common
+/// The `solve` command
 ///
 /// # Arguments
-/// - 'vec1' - A vector with numbers <i32> in it
-/// - 'vec2' - A vector with numbers <i32> in it
-/// - 'vec3' - A vector with numbers <i32> in it
-fn common_numbers(vec1: &Vec<i32>, vec2: &Vec<i32>, vec3: &Vec<i32>) -> Vec<i32> {
-    let set1: HashSet<_> = vec1.iter().cloned().collect();
-    let set2: HashSet<_> = vec2.iter().cloned().collect();
-    let set3: HashSet<_> = vec3.iter().cloned().collect();
-
-    // HashSet has a intersection variable, that gets us all elements which are in both sets
-    // .cloned() = clone the values, because else it would still reference the old values
-    // .collect::<T>()  = a method to converte an iterater into a specific type
-    // <HashSet<_>>() = the hasSet type, the underscore is a type inference placeholder
-    let intersection_set = set1.intersection(&set2).cloned().collect::<HashSet<_>>()
-        .intersection(&set3).cloned().collect::<HashSet<_>>();
-
-    intersection_set.into_iter().collect()
+/// * `arguments` - The arguments after the command
+fn solve(arguments: &[String]) -> Result<bool, CliError> {
+    let arguments = Arguments::parse(arguments, &["format", "box", "backend"], &[])?;
+    let format = arguments.format()?;
+    let read = |value: &str| match value {
+        "auto" => Some(SolverBackend::Auto),
+        "bitmask" => Some(SolverBackend::Bitmask),
+        "backtracking" => Some(SolverBackend::Backtracking),
+        "dlx" => Some(SolverBackend::Dlx),
+        _ => None,
+    };
+    let backend = arguments.get("backend", read, "auto, bitmask, backtracking or dlx")?.unwrap_or_default();
+
+    for_each_sudoku(&arguments, |line, sudoku| match get_solution_with(&sudoku, backend) {
+        Ok(solution) => {
+            write_sudoku(&solution, format);
+            true
+        }
+        Err(error) => {
+            eprintln!("line {}: {}", line, error);
+            false
+        }
+    })
 }
 
-///A Sudoku consists of several subgrids. This function returns the top left and bottom right
-/// coordinates of the subgrid which contains the location (xco, yco)
+/// The `grade` command
 ///
 /// # Arguments
-/// 'xco' - The column coordinate of the spot we want to check
-/// 'yco' - The row coordinate of the spot we want to check
-fn get_subgrid_coor(xco : i32, yco:i32) -> (i32, i32, i32, i32){
-    let x1 = (xco / 3) * 3;
-    let y1 = (yco / 3) * 3;
-    let x2 = x1 + 3;
-    let y2 = y1 + 3;
-
-    let coordinates = (x1, y1, x2, y2);
-    coordinates
-}
-
-/// Given a location on a sudoku (x, y), return all of the numbers which can still be put inside
-/// the field, whilst still following the rules of sudoku (no duplicates in row, column or subgrid)
-///
-///  # Arguments
-/// * `sudoku` - A reference to a 2D vector (`&Vec<Vec<i32>>`) representing the Sudoku grid.
-/// 'xco' - the column coordinate of the spot we want to check
-/// 'yco' - the row coordinate of the spot we want to check
-fn get_all_missing_numbers(sudoku: &Vec<Vec<i32>>, xco : i32, yco:i32) -> Vec<i32>{
-
-    // get the values of the row and column
-    let row = get_row(&sudoku, yco);
-    let column = get_column(&sudoku, xco);
-
-    // get the values of the subgrid
-    let coor = get_subgrid_coor(xco,yco);
-    let subgrid = get_subgrid(&sudoku, coor.1, coor.3, coor.0, coor.2);
-
-    let missing_numbers_column = find_missing_numbers(&column);
-    let missing_numbers_row = find_missing_numbers(&row);
-    let missing_numbers_subgrid = find_missing_numbers(&subgrid);
-
-    let missing_numbers_total = common_numbers(&missing_numbers_row, &missing_numbers_column, &missing_numbers_subgrid);
-    missing_numbers_total
+/// * `arguments` - The arguments after the command
+fn grade(arguments: &[String]) -> Result<bool, CliError> {
+    let arguments = Arguments::parse(arguments, &READ_OPTIONS, &[])?;
+
+    for_each_sudoku(&arguments, |line, sudoku| {
+        let grade = get_solution(&sudoku)
+            .map_err(|error| error.to_string())
+            .and_then(|_| grade_sudoku(&sudoku).map_err(|error| error.to_string()));
+        match grade {
+            Ok(grade) => {
+                println!("{}", grade);
+                true
+            }
+            Err(error) => {
+                eprintln!("line {}: {}", line, error);
+                false
+            }
+        }
+    })
 }
 
-/// Returns a vector containing the locations of all empty grids inside of a sudoku
+/// The `validate` command, which writes for every sudoku whether it is valid, and why not
 ///
 /// # Arguments
-/// * `sudoku` - A reference to a 2D vector (`&Vec<Vec<i32>>`) representing the Sudoku grid.
-fn get_all_empty_fields(sudoku: &Vec<Vec<i32>>) -> Vec<(usize, usize)>{
-    let mut empty_spots = vec![];
-
-    let n = sudoku.len();
-    let m = sudoku[0].len();
-
-    for i in 0..n {
-        for j in 0..m {
-            if sudoku[i][j] == 0{
-                empty_spots.push((j, i));   // column and row
-            }
+/// * `arguments` - The arguments after the command
+fn validate(arguments: &[String]) -> Result<bool, CliError> {
+    let arguments = Arguments::parse(arguments, &READ_OPTIONS, &[])?;
+
+    for_each_sudoku(&arguments, |line, sudoku| {
+        let problem = match get_solution(&sudoku) {
+            Err(SolveError::DuplicateGiven { number, first, second }) =>
+                Some(format!("{} is given twice, at {:?} and {:?}", number, first, second)),
+            Err(SolveError::NoSolution) => Some("no solution".to_string()),
+            Ok(_) if !has_unique_solution(&sudoku) => Some("more than one solution".to_string()),
+            Ok(_) => None,
+        };
+        match &problem {
+            None => println!("line {}: valid", line),
+            Some(problem) => println!("line {}: invalid, {}", line, problem),
         }
-    }
-    empty_spots
+        problem.is_none()
+    })
 }
 
-///Returns True/False based on whether the current sudoku has all its spaces filled in, and follows
-/// all of the rules (no duplicate numbers in rows, columns and subgrids)
+/// The `canonicalize` command
 ///
 /// # Arguments
-/// * `sudoku` - A reference to a 2D vector (`&Vec<Vec<i32>>`) representing the Sudoku grid.
-fn check_if_sudoku_solved(sudoku: &Vec<Vec<i32>>) -> bool{
-
-    // Check if there are any open spots left. If yes, we return false, this hasn't been solved
-    let empty_loc = get_all_empty_fields(&sudoku);
-    if empty_loc.len() > 0{
-        return false;
-    }
-
-    // Check all rows, columns and subgrids for valid answers.
-    let n = sudoku.len();                              // height sudoku
-    let m = sudoku[0].len();                           // width sudoku
-    let numbers = vec![1, 2, 3, 4, 5, 6, 7, 8, 9];  // the numbers we want to check for
-
-    // check all rows
-    for i in 0..n {
-        let row = get_row(&sudoku, i as i32);
-        let all_numbers_present = numbers.iter().all(|&num| row.contains(&num));
-        if !all_numbers_present{
-            return false;
-        }
+/// * `arguments` - The arguments after the command
+fn canonicalize(arguments: &[String]) -> Result<bool, CliError> {
+    let arguments = Arguments::parse(arguments, &["format", "box"], &[])?;
+    let format = arguments.format()?;
+    let too_large = |size: usize| CliError::Usage(format!(
+        "canonical forms can be found for sudokus of up to {}x{}, not {}x{}", MAX_CANONICAL_SIZE, MAX_CANONICAL_SIZE, size, size,
+    ));
+    if let Some(box_shape) = arguments.box_shape()?.filter(|shape| shape.size() > MAX_CANONICAL_SIZE) {
+        return Err(too_large(box_shape.size()));
     }
 
-    // check all columns
-    for i in 0..m{
-        let column = get_column(&sudoku, i as i32);
-        let all_numbers_present = numbers.iter().all(|&num| column.contains(&num));
-        if !all_numbers_present{
+    // the first sudoku that is too large stops the command, and the rest is skipped
+    let mut too_large_size = None;
+    let all_fine = for_each_sudoku(&arguments, |_, sudoku| {
+        if too_large_size.is_some() || sudoku.size() > MAX_CANONICAL_SIZE {
+            too_large_size = too_large_size.or(Some(sudoku.size()));
             return false;
         }
+        write_sudoku(&canonical_form(&sudoku), format);
+        true
+    })?;
+    match too_large_size {
+        Some(size) => Err(too_large(size)),
+        None => Ok(all_fine),
     }
-
-    // check all grids
-    for i in 0..sqrt(m){
-        for j in 0..sqrt(n){
-            let subgrid = get_subgrid(&sudoku, (i * 3) as i32,  ((i + 1) * 3) as i32, (j * 3) as i32,((j + 1) * 3) as i32);
-            let all_numbers_present = numbers.iter().all(|&num| subgrid.contains(&num));
-            if !all_numbers_present{
-                return false;
-            }
-        }
-    }
-
-    // if all conditions are met, return true.
-    true
 }
 
-
-/// Generates and returns a sudoku which has been completely filled in
-///
-/// # Arguments
-/// 'width' - The total width of the sudoku we want to generate (for a standard sudoku  = 9)
-/// 'height' - The total height of the sudoku we want to generate (for a standard sudoku = 9)
-fn generate_full_sudoku(width : usize, height : usize) -> Vec<Vec<i32>>{
-    // This will generate a sudoku which is completely filled in and valid
-
-
-    // usize = unsigned integer
-    let mut sudoku: Vec<Vec<i32>> = vec![vec![0; width]; height];
-
-    // get the numbers one through nine in random order
-    let mut numbers: Vec<i32> = (1..10).collect();
-    numbers.shuffle(&mut rand::thread_rng());
-
-    // Fill in the sudoku
-    sudoku = fill_row(sudoku, &numbers, 0, 0);
-    sudoku = fill_row(sudoku, &numbers, 1, 3);
-    sudoku = fill_row(sudoku, &numbers, 2, 6);
-    sudoku = fill_row(sudoku, &numbers, 3, 1);
-    sudoku = fill_row(sudoku, &numbers, 4, 4);
-    sudoku = fill_row(sudoku, &numbers, 5, 7);
-    sudoku = fill_row(sudoku, &numbers, 6, 2);
-    sudoku = fill_row(sudoku, &numbers, 7, 5);
-    sudoku = fill_row(sudoku, &numbers, 8, 8);
-
-    // flip all of the rows within the sub grids
-    sudoku = flip_all_rows(sudoku);
-
-    // flip all of the columns within the sub grids
-    sudoku = flip_all_columns(sudoku);
-
-    // flip all large grid rows (for example, flip all rows with [0,1,2] with [6,7,8])
-    sudoku = flip_grid_rows(sudoku);
-
-    // flip all large grid columns (for example, flip all columns with [0,1,2] with [6,7,8])
-    sudoku = flip_grid_columns(sudoku);
-
-    // randomly rotate 0, 90, 180, 270 degrees
-    random_rotate(&mut sudoku);
-    // print_sudoku(&sudoku);
-
-    // return the two dimensional array
-    sudoku
-
+/// One of the transforms asked for on the command line
+enum Step {
+    /// Swap the rows and the columns
+    Transpose,
+    /// Turn clockwise by this many quarter turns
+    Rotate(u32),
+    /// Mirror in a line
+    Reflect(Reflection),
+    /// Replace every number d with the number at d - 1
+    Relabel(Vec<Digit>),
+    /// A random transform, drawn from this seed
+    Random(u64),
 }
 
-/// Solves a sudoku, and returns True if it can be solved and False if it can't
+/// The `transform` command
 ///
 /// # Arguments
-/// * `sudoku_check` - A reference to a 2D vector (`&Vec<Vec<i32>>`) representing the Sudoku grid we want to solve
-fn solve_sudoku(sudoku_check : &Vec<Vec<i32>>) -> bool{
-
-    let mut sudoku_to_solve: Vec<Vec<i32>> = sudoku_check.clone();
-
-    // for each empty spot, see if there is only 1 other number we can fill in. If so, we will
-    // recheck all empty spots after we have filled it in.
-    loop {
-        let mut found:bool = false;
-        let all_empty_loc = get_all_empty_fields(&sudoku_to_solve);
-        for loc in all_empty_loc.iter() {
-            if sudoku_to_solve[loc.1][loc.0] == 0 {
-                // find out how many numbers we can get
-                let all_missing_numbers = get_all_missing_numbers(&sudoku_to_solve, loc.0 as i32 ,loc.1 as i32);
-                if all_missing_numbers.len() == 1 {
-                    sudoku_to_solve[loc.1][loc.0] = all_missing_numbers[0];
-                    found = true;
-                }
-            }
-        }
-        if !found{
-            break
-        }
+/// * `arguments` - The arguments after the command
+fn transform(arguments: &[String]) -> Result<bool, CliError> {
+    let arguments = Arguments::parse(arguments, &["format", "box", "rotate", "reflect", "relabel", "seed"], &["transpose"])?;
+    let format = arguments.format()?;
+
+    // the transforms, in the order of the usage
+    let mut steps = vec![];
+    if arguments.flag("transpose") {
+        steps.push(Step::Transpose);
     }
-
-    sudoku_to_solve[0][0] = 0;
-
-    // check if we need to do the forward propagations method
-    let all_empty_loc = get_all_empty_fields(&sudoku_to_solve);
-    if all_empty_loc.len()>0{
-
-        let mut i :i32 = -1;
-        loop{
-            i+=1;
-
-            let xloc = all_empty_loc[i as usize].0;
-            let yloc = all_empty_loc[i as usize].1;
-            let var_check = sudoku_to_solve[yloc][xloc];
-
-            // we have gone of the edge, so we must take a step back
-            if var_check == 9{
-                sudoku_to_solve[yloc][xloc] = 0;
-                i -= 2;
-                if i < -1{
-                    break;
-                }
-            }
-            else{
-                sudoku_to_solve[yloc][xloc] += 1;
-                let valid = is_loc_valid(&sudoku_to_solve, xloc as i32, yloc as i32);
-                if !valid{
-                    i -= 1;
-                }
-            }
-
-            if i>=(all_empty_loc.len()-1) as i32{
-                break;
-            }
-        }
+    let read_turns = |value: &str| value.parse().ok().filter(|turns| (1..=3).contains(turns));
+    if let Some(turns) = arguments.get("rotate", read_turns, "1, 2 or 3")? {
+        steps.push(Step::Rotate(turns));
+    }
+    if let Some(reflection) = arguments.get("reflect", read_reflection, "a line like left-right")? {
+        steps.push(Step::Reflect(reflection));
+    }
+    let read_digits = |value: &str| value.chars().map(|digit| digit.to_digit(36).filter(|&digit| digit > 0).map(|digit| digit as u8)).collect();
+    if let Some(digits) = arguments.get("relabel", read_digits, "the numbers in their new order, like 987654321")? {
+        steps.push(Step::Relabel(digits));
+    }
+    if let Some(seed) = arguments.number("seed")? {
+        steps.push(Step::Random(seed));
+    }
+    if steps.is_empty() {
+        return Err(CliError::Usage("transform needs at least one of --transpose, --rotate, --reflect, --relabel and --seed".to_string()));
     }
 
-    // check if we solved the sudoku
-    let solved = check_if_sudoku_solved(&sudoku_to_solve);
-
-    solved
+    let mut rngs: Vec<_> = steps.iter().map(|step| match step {
+        Step::Random(seed) => Some(seeded_rng(*seed)),
+        _ => None,
+    }).collect();
+    for_each_sudoku(&arguments, |line, sudoku| {
+        let mut transformed = sudoku;
+        for (step, rng) in steps.iter().zip(&mut rngs) {
+            let box_shape = transformed.box_shape();
+            let transform = match step {
+                Step::Transpose => Transform::transpose(box_shape),
+                Step::Rotate(turns) => Transform::rotate(box_shape, *turns),
+                Step::Reflect(reflection) => Transform::reflect(box_shape, *reflection),
+                Step::Random(_) => Transform::random(box_shape, rng.as_mut().unwrap()),
+                Step::Relabel(digits) => match Transform::relabel(box_shape, digits) {
+                    Ok(transform) => transform,
+                    Err(error) => {
+                        eprintln!("line {}: can't relabel: {}", line, error);
+                        return false;
+                    }
+                },
+            };
+            transformed = transform.apply(&transformed);
+        }
+        write_sudoku(&transformed, format);
+        true
+    })
 }
 
-/// Returns a a sudoku with empty spaces that we can solve, based on a filled in example
-///  # Arguments
+/// Reads a difficulty, in any case
 ///
-/// * `filled_sudoku` - A reference to a 2D vector (`&Vec<Vec<i32>>`) representing the Sudoku grid. This is completely filled in
-/// 'num_to_delete' - How many fields we want to make empty in our new sudoku
-fn generate_sudoku_to_solve(filled_sudoku : &Vec<Vec<i32>>, num_to_delete: i32) -> Vec<Vec<i32>>{
-
-    // copy the filled sudoku
-    let mut sudoku_to_solve: Vec<Vec<i32>> = filled_sudoku.clone();
-
-    // start removing values
-    let n = sudoku_to_solve.len();                              // height sudoku
-    let m = sudoku_to_solve[0].len();                           // width sudoku
-
-    let mut num_deleted = 0;                    // the number of grids we have deleted
-    while num_deleted < num_to_delete {
-
-        let mut old_val = 0;
-        let mut xco = 0;
-        let mut yco = 0;
-        loop {
-
-            xco = generate_random_number(0..m as i32);
-            yco = generate_random_number(0..n as i32);
-            if sudoku_to_solve[yco as usize][xco as usize] !=0{
-                old_val = sudoku_to_solve[yco as usize][xco as usize];
-                sudoku_to_solve[yco as usize][xco as usize] = 0;
-                break;
-            }
-        }
-
-        // we try to solve, if we can, we will leave it removed
-        let solved = solve_sudoku(&sudoku_to_solve);
-        if !solved{
-            //reset and try again.
-            sudoku_to_solve[yco as usize][xco as usize]=old_val;
-        }
-        else {
-            num_deleted += 1;
-        }
-    }
-    sudoku_to_solve
+/// # Arguments
+/// * `value` - The name of the difficulty
+fn read_difficulty(value: &str) -> Option<Difficulty> {
+    Difficulty::ALL.into_iter().find(|difficulty| difficulty.to_string().eq_ignore_ascii_case(value))
 }
 
-
-
-/// Prints a Sudoku grid represented by a 2D vector of integers.
+/// Reads a line to mirror in
 ///
 /// # Arguments
-///
-/// * `sudoku` - A reference to a 2D vector (`&Vec<Vec<i32>>`) representing the Sudoku grid.
-///
-/// # Example
-///
-/// ```
-/// let sudoku_grid = vec![
-///     vec![5, 3, 0, 0, 7, 0, 0, 0, 0],
-///     vec![6, 0, 0, 1, 9, 5, 0, 0, 0],
-///     vec![0, 9, 8, 0, 0, 0, 0, 6, 0],
-///     vec![8, 0, 0, 0, 6, 0, 0, 0, 3],
-///     vec![4, 0, 0, 8, 0, 3, 0, 0, 1],
-///     vec![7, 0, 0, 0, 2, 0, 0, 0, 6],
-///     vec![0, 6, 0, 0, 0, 0, 2, 8, 0],
-///     vec![0, 0, 0, 4, 1, 9, 0, 0, 5],
-///     vec![0, 0, 0, 0, 8, 0, 0, 7, 9],
-/// ];
-///
-/// print_sudoku(&sudoku_grid);
-/// ```
-///
-/// This will print the Sudoku grid to the console, with empty cells represented by spaces.
-///
-/// ```
-///5   3           7
-///6           1   9   5
-///    9   8                   6
-///8               6               3
-///4           8       3           1
-///7               2               6
-///   6                   2   8
-///         4   1   9           5
-///         8           7   9
-///
-/// ```
-///
-/// Note: In the printed output, empty cells are represented by spaces.
-///
-/// # Panics
-///
-/// This function does not panic under normal circumstances.
-///
-/// # Safety
-///
-/// This function assumes that the input Sudoku grid is a valid representation.
-fn print_sudoku(sudoku : &Vec<Vec<i32>>) {
-    for row in sudoku {
-        for element in row {
-            if *element==0{             // get value from reference
-                print!("{:4}", " ")
-            }
-            else {
-                print!("{:4}", element);
-            }
-        }
-        println!();
+/// * `value` - The name of the line
+fn read_reflection(value: &str) -> Option<Reflection> {
+    match value {
+        "left-right" => Some(Reflection::LeftRight),
+        "top-bottom" => Some(Reflection::TopBottom),
+        "main-diagonal" => Some(Reflection::MainDiagonal),
+        "anti-diagonal" => Some(Reflection::AntiDiagonal),
+        _ => None,
     }
-    println!();
 }
 
-
-
-fn main() {
-
-    // Create a filled in sudoku
-    let sudoku = generate_full_sudoku(9, 9);
-    println!("Filled sudoku");
-    print_sudoku(&sudoku);
-
-    // Start the process of creating an non filled
-    let sudoku_to_solve = generate_sudoku_to_solve(&sudoku, 50);
-    println!("To Solve Sudoku");
-    print_sudoku(&sudoku_to_solve);
-
+/// Reads a symmetry, where a line to mirror in stands for the mirror symmetry in that line
+///
+/// # Arguments
+/// * `value` - The name of the symmetry
+fn read_symmetry(value: &str) -> Option<Symmetry> {
+    match value {
+        "none" => Some(Symmetry::None),
+        "half-turn" => Some(Symmetry::HalfTurn),
+        "quarter-turn" => Some(Symmetry::QuarterTurn),
+        "dihedral" => Some(Symmetry::Dihedral),
+        _ => read_reflection(value).map(Symmetry::Mirror),
+    }
 }
@@ -0,0 +1,27 @@
+/*
+    Random : The random numbers used when generating sudokus. All of them come from a generator
+    that is passed in, so that a sudoku can be generated again from the seed it was made with.
+    The ChaCha8 generator gives the same numbers for the same seed on every platform, which the
+    standard generator of the rand crate doesn't promise.
+ */
+
+use rand::{Rng, SeedableRng};   // random numbers
+use rand_chacha::ChaCha8Rng;
+
+
+/// The random number generator used to generate sudokus
+pub type SudokuRng = ChaCha8Rng;
+
+/// Returns a random number generator which always gives the same numbers for the same seed
+///
+/// # Arguments
+/// * `seed` - The seed to start the generator from
+pub fn seeded_rng(seed: u64) -> SudokuRng {
+    SudokuRng::seed_from_u64(seed)
+}
+
+/// Returns a new random seed, for when any sudoku will do
+pub fn random_seed() -> u64 {
+    rand::thread_rng().gen()
+}
+
@@ -0,0 +1,390 @@
+/*
+    Solver : Solves sudokus by backtracking. Besides a single solution, it can walk through all
+    solutions of a sudoku, which is how we check that a sudoku has exactly one solution.
+
+    The search itself can be done by one of three backends. The bitmask solver solves a typical
+    9x9 sudoku in microseconds, but it only guesses and doesn't reason, so it gets very slow on
+    sparse sudokus of 16x16 and larger. Sudokus wider than 64x64 don't fit in its bitmasks, and are
+    solved by the slower backtracking on the grid itself instead. The dancing links of the dlx
+    module are the third backend, which is best at large sudokus. By default the backend is picked
+    by the size of the sudoku: the bitmask solver below 16x16, and dancing links from there on.
+ */
+
+use crate::bitmask_solver::{self, BitmaskSearch};
+use crate::dlx::{solutions_with_rules, DlxSolutions, SUDOKU_RULES};
+use crate::grid::{Digit, Grid};
+
+
+/// The reasons why `get_solution` can fail to return a solved sudoku
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum SolveError {
+    /// The sudoku follows the rules, but there is no way to fill in all of its empty fields
+    NoSolution,
+    /// A number is given twice in the same row, column or subgrid, at the fields `first` and
+    /// `second` (as (row, column)), so the sudoku breaks the rules before it is even solved
+    DuplicateGiven { number: Digit, first: (usize, usize), second: (usize, usize) },
+}
+
+impl std::fmt::Display for SolveError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            SolveError::NoSolution => write!(f, "the sudoku has no solution"),
+            SolveError::DuplicateGiven { number, first, second } =>
+                write!(f, "the number {} is given twice in the same row, column or subgrid, at {:?} and {:?}", number, first, second),
+        }
+    }
+}
+
+impl std::error::Error for SolveError {}
+
+/// The ways the solver can search for solutions. They all find the same solutions, but not
+/// always in the same order, and each is fastest at something else.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash)]
+pub enum SolverBackend {
+    /// `Bitmask` for sudokus smaller than 16x16, and `Dlx` for 16x16 and larger
+    #[default]
+    Auto,
+    /// Backtracking with the used numbers as bitmasks, always continuing with the field that has
+    /// the fewest options. The fastest for ordinary sudokus. Sudokus wider than 64x64 are solved
+    /// with `Backtracking` instead.
+    Bitmask,
+    /// Backtracking on the grid itself, which is slow but simple
+    Backtracking,
+    /// Algorithm X with dancing links, which is good at counting the solutions of large sudokus
+    Dlx,
+}
+
+/// The width from which `SolverBackend::Auto` searches with dancing links, as the bitmask solver
+/// is far slower on sparse 16x16 and larger sudokus
+const AUTO_DLX_SIZE: usize = 16;
+
+impl SolverBackend {
+    /// Returns the backend that does the search for a sudoku of this width, which is only
+    /// different from the backend itself for `Auto`
+    ///
+    /// # Arguments
+    /// * `size` - The width of the sudoku
+    fn for_size(self, size: usize) -> SolverBackend {
+        match self {
+            SolverBackend::Auto if size >= AUTO_DLX_SIZE => SolverBackend::Dlx,
+            SolverBackend::Auto => SolverBackend::Bitmask,
+            backend => backend,
+        }
+    }
+}
+
+/// Solves a sudoku, and returns True if it can be solved and False if it can't
+///
+/// # Arguments
+/// * `sudoku_check` - A reference to the Sudoku grid we want to solve
+pub fn solve_sudoku(sudoku_check : &Grid) -> bool{
+    get_solution(sudoku_check).is_ok()
+}
+
+/// Solves a sudoku with the given backend, and returns True if it can be solved and False if it
+/// can't
+///
+/// # Arguments
+/// * `sudoku_check` - A reference to the Sudoku grid we want to solve
+/// * `backend` - The way to search for a solution
+pub fn solve_sudoku_with(sudoku_check : &Grid, backend: SolverBackend) -> bool{
+    get_solution_with(sudoku_check, backend).is_ok()
+}
+
+/// Solves a sudoku, and returns the completely filled in sudoku. If the sudoku can't be solved,
+/// the reason why is returned instead. The given numbers are never changed, and a sudoku which
+/// gives the same number twice in a row, column or subgrid is rejected before searching.
+///
+/// # Arguments
+/// * `sudoku_check` - A reference to the Sudoku grid we want to solve
+pub fn get_solution(sudoku_check : &Grid) -> Result<Grid, SolveError>{
+    get_solution_with(sudoku_check, SolverBackend::default())
+}
+
+/// Solves a sudoku with the given backend, and returns the completely filled in sudoku. If the
+/// sudoku can't be solved, the reason why is returned instead.
+///
+/// # Arguments
+/// * `sudoku_check` - A reference to the Sudoku grid we want to solve
+/// * `backend` - The way to search for a solution
+pub fn get_solution_with(sudoku_check : &Grid, backend: SolverBackend) -> Result<Grid, SolveError>{
+    check_givens(sudoku_check)?;
+    match backend.for_size(sudoku_check.size()) {
+        SolverBackend::Backtracking => get_solution_on_grid(sudoku_check),
+        _ => solutions_with(sudoku_check, backend).next().ok_or(SolveError::NoSolution),
+    }
+}
+
+/// Solves a sudoku by backtracking on the grid itself, which works for sudokus of any size
+///
+/// # Arguments
+/// * `sudoku_check` - A reference to the Sudoku grid we want to solve
+fn get_solution_on_grid(sudoku_check : &Grid) -> Result<Grid, SolveError>{
+
+    let mut sudoku_to_solve: Grid = sudoku_check.clone();
+
+    // for each empty spot, see if there is only 1 other number we can fill in. If so, we will
+    // recheck all empty spots after we have filled it in.
+    loop {
+        let mut found:bool = false;
+        let all_empty_loc = sudoku_to_solve.empty_fields();
+        for &(row, column) in all_empty_loc.iter() {
+            if sudoku_to_solve.get(row, column).is_none() {
+                // find out how many numbers we can get
+                let all_missing_numbers = sudoku_to_solve.candidates(row, column);
+                if all_missing_numbers.len() == 1 {
+                    sudoku_to_solve.set(row, column, Some(all_missing_numbers[0]));
+                    found = true;
+                }
+            }
+        }
+        if !found{
+            break
+        }
+    }
+
+    // walk through the fields that are still empty, and give each the next number that doesn't
+    // break the rules. Only these fields are ever changed, so the givens stay as they are.
+    let all_empty_loc = sudoku_to_solve.empty_fields();
+    let mut i: usize = 0;
+    while i < all_empty_loc.len() {
+        let (row, column) = all_empty_loc[i];
+        let previous_number = sudoku_to_solve.get(row, column).unwrap_or(0);
+        sudoku_to_solve.set(row, column, None);
+
+        match sudoku_to_solve.candidates(row, column).into_iter().find(|&number| number > previous_number) {
+            Some(number) => {
+                sudoku_to_solve.set(row, column, Some(number));
+                i += 1;
+            }
+            // we have gone of the edge, so we must take a step back (or give up, if we are
+            // back at the first field)
+            None => {
+                if i == 0 {
+                    return Err(SolveError::NoSolution);
+                }
+                i -= 1;
+            }
+        }
+    }
+
+    // every number was checked against the rules when it was filled in, and the givens were
+    // checked before we started, so the sudoku is solved
+    debug_assert!(sudoku_to_solve.is_solved());
+    Ok(sudoku_to_solve)
+}
+
+/// Returns an error if a number is given twice in the same row, column or subgrid, as such a
+/// sudoku has no solution, and searching for one would be a waste of time
+///
+/// # Arguments
+/// * `sudoku` - A reference to the Sudoku grid.
+fn check_givens(sudoku: &Grid) -> Result<(), SolveError>{
+    let size = sudoku.size();
+    let box_shape = sudoku.box_shape();
+
+    // for every row, column and subgrid (in that order) and every number, where we saw it first
+    let mut first_seen: Vec<Option<(usize, usize)>> = vec![None; 3 * size * size];
+    for (row, column, value) in sudoku.fields(){
+        let Some(number) = value else { continue };
+        for unit in [row, size + column, 2 * size + box_shape.subgrid_of(row, column)]{
+            let seen = &mut first_seen[unit * size + number as usize - 1];
+            if let Some(first) = *seen {
+                return Err(SolveError::DuplicateGiven { number, first, second: (row, column) });
+            }
+            *seen = Some((row, column));
+        }
+    }
+    Ok(())
+}
+
+/// Returns the empty field with the fewest numbers that can still be filled in, together with
+/// those numbers, or None if the sudoku has no empty fields left. Trying the most constrained
+/// field first keeps the number of guesses during backtracking small.
+///
+/// # Arguments
+/// * `sudoku` - A reference to the Sudoku grid.
+fn get_most_constrained_field(sudoku: &Grid) -> Option<(usize, usize, Vec<Digit>)>{
+    let mut best: Option<(usize, usize, Vec<Digit>)> = None;
+    for (row, column) in sudoku.empty_fields(){
+        let missing_numbers = sudoku.candidates(row, column);
+        if best.as_ref().is_none_or(|(_, _, best_numbers)| missing_numbers.len() < best_numbers.len()){
+            // a field with 0 or 1 options can't be beaten, so there is no need to look further
+            let can_stop = missing_numbers.len() <= 1;
+            best = Some((row, column, missing_numbers));
+            if can_stop{
+                break;
+            }
+        }
+    }
+    best
+}
+
+/// An iterator over all solutions of a sudoku, which only searches for the next solution when it
+/// is asked for. Created with `solutions`.
+pub struct Solutions {
+    search: Search,
+}
+
+/// The search behind `Solutions`
+enum Search {
+    Bitmask(BitmaskSearch),
+    Grid(GridSearch),
+    Dlx(DlxSolutions),
+}
+
+impl Iterator for Solutions {
+    type Item = Grid;
+
+    fn next(&mut self) -> Option<Grid> {
+        match &mut self.search {
+            Search::Bitmask(search) => search.next_solution(),
+            Search::Grid(search) => search.next(),
+            Search::Dlx(search) => search.next(),
+        }
+    }
+}
+
+/// A backtracking search on the grid itself, with an explicit stack, so that it can stop after
+/// every solution and pick up where it left off
+struct GridSearch {
+    /// The sudoku as far as it has been filled in by the search
+    sudoku: Grid,
+    /// For every field we have guessed, its row, its column and the numbers we still have to try
+    stack: Vec<(usize, usize, Vec<Digit>)>,
+    /// Whether the first field has been picked yet
+    started: bool,
+}
+
+impl Iterator for GridSearch {
+    type Item = Grid;
+
+    fn next(&mut self) -> Option<Grid> {
+        if !self.started{
+            self.started = true;
+            match get_most_constrained_field(&self.sudoku) {
+                // the sudoku was already filled in, so it is its own (only) solution
+                None => return Some(self.sudoku.clone()),
+                Some(field) => self.stack.push(field),
+            }
+        }
+
+        loop {
+            // when the stack is empty, every option has been tried
+            let (row, column, numbers) = self.stack.last_mut()?;
+            let (row, column) = (*row, *column);
+
+            match numbers.pop() {
+                // all numbers have been tried for this field, so take a step back
+                None => {
+                    self.sudoku.set(row, column, None);
+                    self.stack.pop();
+                }
+                Some(number) => {
+                    self.sudoku.set(row, column, Some(number));
+                    match get_most_constrained_field(&self.sudoku) {
+                        // no empty fields left, so this is a solution
+                        None => return Some(self.sudoku.clone()),
+                        // a field without options is a dead end, so try the next number instead
+                        Some((_, _, next_numbers)) if next_numbers.is_empty() => {}
+                        Some(field) => self.stack.push(field),
+                    }
+                }
+            }
+        }
+    }
+}
+
+/// Returns an iterator over all solutions of a sudoku. The solutions are searched one at a time,
+/// so use `.take(n)` to look at a few of them without searching for all of them (a nearly empty
+/// sudoku has an enormous number of solutions).
+///
+/// # Arguments
+/// * `sudoku` - A reference to the Sudoku grid.
+pub fn solutions(sudoku: &Grid) -> Solutions{
+    solutions_with(sudoku, SolverBackend::default())
+}
+
+/// Returns an iterator over all solutions of a sudoku, which are searched with the given backend
+/// (see `solutions`)
+///
+/// # Arguments
+/// * `sudoku` - A reference to the Sudoku grid.
+/// * `backend` - The way to search for solutions
+pub fn solutions_with(sudoku: &Grid, backend: SolverBackend) -> Solutions{
+    let search = match backend.for_size(sudoku.size()) {
+        SolverBackend::Bitmask if sudoku.size() <= bitmask_solver::MAX_SIZE => Search::Bitmask(BitmaskSearch::new(sudoku)),
+        SolverBackend::Dlx => Search::Dlx(solutions_with_rules(sudoku, &SUDOKU_RULES)),
+        // a sudoku which already breaks the rules has no solutions at all, so we pretend the
+        // search has already started with nothing left to try
+        _ => Search::Grid(GridSearch {
+            sudoku: sudoku.clone(),
+            stack: vec![],
+            started: !sudoku.is_valid(),
+        }),
+    };
+    Solutions { search }
+}
+
+/// Counts the number of solutions of a sudoku, but stops counting once `limit` solutions have been
+/// found, as counting all solutions of a nearly empty sudoku would take forever. Counting to 2 is
+/// enough to know whether a sudoku has exactly one solution.
+///
+/// # Arguments
+/// * `sudoku` - A reference to the Sudoku grid.
+/// * `limit` - The number of solutions after which we stop counting
+pub fn count_solutions(sudoku: &Grid, limit: usize) -> usize{
+    solutions(sudoku).take(limit).count()
+}
+
+/// Counts the number of solutions of a sudoku with the given backend, up to `limit` (see
+/// `count_solutions`)
+///
+/// # Arguments
+/// * `sudoku` - A reference to the Sudoku grid.
+/// * `limit` - The number of solutions after which we stop counting
+/// * `backend` - The way to search for solutions
+pub fn count_solutions_with(sudoku: &Grid, limit: usize, backend: SolverBackend) -> usize{
+    solutions_with(sudoku, backend).take(limit).count()
+}
+
+/// Returns True if the sudoku has exactly one solution, which is what we want for a sudoku that
+/// people are going to solve. Works on sudokus from any source, not just generated ones.
+///
+/// # Arguments
+/// * `sudoku` - A reference to the Sudoku grid.
+pub fn has_unique_solution(sudoku: &Grid) -> bool{
+    count_solutions(sudoku, 2) == 1
+}
+
+/// Returns True if the sudoku has exactly one solution, and every given number is needed for
+/// that: emptying any one of them gives a sudoku with more than one solution
+///
+/// # Arguments
+/// * `sudoku` - A reference to the Sudoku grid.
+pub fn is_minimal(sudoku: &Grid) -> bool{
+    if !has_unique_solution(sudoku){
+        return false;
+    }
+    let mut reduced = sudoku.clone();
+    sudoku.fields().filter(|(_, _, value)| value.is_some()).all(|(row, column, value)| {
+        reduced.set(row, column, None);
+        let needed = count_solutions(&reduced, 2) > 1;
+        reduced.set(row, column, value);
+        needed
+    })
+}
+
+/// Returns the locations (row, column) of all fields which have a different value in the two
+/// sudokus, for example to highlight where two solutions of the same sudoku differ
+///
+/// # Arguments
+/// * `sudoku1` - A reference to the first Sudoku grid.
+/// * `sudoku2` - A reference to the second Sudoku grid.
+pub fn get_differing_fields(sudoku1: &Grid, sudoku2: &Grid) -> Vec<(usize, usize)>{
+    sudoku1.fields()
+        .zip(sudoku2.fields())
+        .filter(|((_, _, value1), (_, _, value2))| value1 != value2)
+        .map(|((row, column, _), _)| (row, column))
+        .collect()
+}
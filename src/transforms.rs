@@ -0,0 +1,446 @@
+/*
+    Transforms : Ways to change a sudoku without breaking it. Swapping rows within a band,
+    swapping whole bands, the same for columns and stacks, transposing the grid and relabelling the
+    numbers all keep a valid sudoku valid, and keep a sudoku with one solution at one solution.
+    Rotations and reflections are combinations of these.
+
+    A Transform stores all of this as three lists and a flag. Applying it first moves the rows and
+    columns around, so that row r of the new grid is row rows[r] of the old one (and likewise for
+    the columns), then transposes the grid if the flag is set, and at last replaces every number d
+    with digits[d - 1].
+ */
+
+use std::fmt;
+
+use rand::seq::SliceRandom;     // random slices
+use rand::Rng;                  // random numbers
+
+use crate::generator::Puzzle;
+use crate::grading::grade_sudoku;
+use crate::grid::{BoxShape, Digit, Grid};
+
+
+/// The ways a sudoku can be mirrored
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum Reflection {
+    /// Mirror in the vertical line through the middle, swapping left and right
+    LeftRight,
+    /// Mirror in the horizontal line through the middle, swapping top and bottom
+    TopBottom,
+    /// Mirror in the diagonal from the top left to the bottom right
+    MainDiagonal,
+    /// Mirror in the diagonal from the top right to the bottom left
+    AntiDiagonal,
+}
+
+/// The reasons why a transform can't be made
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum TransformError {
+    /// The list should contain every number from `start` up to and including `end` exactly once
+    NotAPermutation { start: usize, end: usize },
+    /// There is no band or stack with this number
+    NoSuchBlock { block: usize, blocks: usize },
+}
+
+impl fmt::Display for TransformError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            TransformError::NotAPermutation { start, end } =>
+                write!(f, "expected every number from {} to {} exactly once", start, end),
+            TransformError::NoSuchBlock { block, blocks } =>
+                write!(f, "there is no block {}, as there are only {}", block, blocks),
+        }
+    }
+}
+
+impl std::error::Error for TransformError {}
+
+/// A change to a sudoku which keeps it valid: any combination of moving rows within their band,
+/// moving bands, moving columns within their stack, moving stacks, transposing and relabelling
+/// the numbers. Transforms can be combined with `then` and undone with `inverse`.
+///
+/// A transform is made for sudokus with one shape of subgrids. Transposing turns rectangular
+/// subgrids on their side, so a transposing transform gives sudokus of the rotated shape.
+///
+/// # Example
+///
+/// ```
+/// use sudoku_creator::{generate_full_sudoku, seeded_rng, Transform};
+///
+/// let mut rng = seeded_rng(1);
+/// let sudoku = generate_full_sudoku(9, 9, &mut rng);
+/// let transform = Transform::random(sudoku.box_shape(), &mut rng);
+///
+/// let shuffled = transform.apply(&sudoku);
+/// assert!(shuffled.is_solved());
+/// assert_eq!(transform.inverse().apply(&shuffled), sudoku);
+/// ```
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+pub struct Transform {
+    /// The shape of the subgrids of the sudokus this transform is for
+    box_shape: BoxShape,
+    /// Whether the grid is transposed, after the rows and columns have been moved
+    transpose: bool,
+    /// For every row of the moved grid, the row of the original grid it comes from
+    rows: Vec<usize>,
+    /// For every column of the moved grid, the column of the original grid it comes from
+    columns: Vec<usize>,
+    /// For every number (minus one), the number it is replaced with
+    digits: Vec<Digit>,
+}
+
+impl Transform {
+    /// Returns the transform which doesn't change anything
+    ///
+    /// # Arguments
+    /// * `box_shape` - The shape of the subgrids of the sudokus the transform is for
+    pub fn identity(box_shape: BoxShape) -> Transform {
+        let size = box_shape.size();
+        Transform {
+            box_shape,
+            transpose: false,
+            rows: (0..size).collect(),
+            columns: (0..size).collect(),
+            digits: (1..=size as Digit).collect(),
+        }
+    }
+
+    /// Returns the transform which replaces every number d with `digits[d - 1]`
+    ///
+    /// # Arguments
+    /// * `box_shape` - The shape of the subgrids of the sudokus the transform is for
+    /// * `digits` - The new number for every number, so the numbers 1 to width in some order
+    pub fn relabel(box_shape: BoxShape, digits: &[Digit]) -> Result<Transform, TransformError> {
+        let size = box_shape.size();
+        let zero_based: Vec<usize> = digits.iter().map(|&digit| (digit as usize).wrapping_sub(1)).collect();
+        if !is_permutation(&zero_based, size) {
+            return Err(TransformError::NotAPermutation { start: 1, end: size });
+        }
+        Ok(Transform { digits: digits.to_vec(), ..Transform::identity(box_shape) })
+    }
+
+    /// Returns the transform which moves the bands (the rows of subgrids) around. Band b of the new
+    /// sudoku is band `order[b]` of the old one.
+    ///
+    /// # Arguments
+    /// * `box_shape` - The shape of the subgrids of the sudokus the transform is for
+    /// * `order` - For every band, the band it comes from
+    pub fn permute_bands(box_shape: BoxShape, order: &[usize]) -> Result<Transform, TransformError> {
+        let rows = permute_blocks(box_shape.size(), box_shape.rows, order)?;
+        Ok(Transform { rows, ..Transform::identity(box_shape) })
+    }
+
+    /// Returns the transform which moves the stacks (the columns of subgrids) around. Stack s of
+    /// the new sudoku is stack `order[s]` of the old one.
+    ///
+    /// # Arguments
+    /// * `box_shape` - The shape of the subgrids of the sudokus the transform is for
+    /// * `order` - For every stack, the stack it comes from
+    pub fn permute_stacks(box_shape: BoxShape, order: &[usize]) -> Result<Transform, TransformError> {
+        let columns = permute_blocks(box_shape.size(), box_shape.columns, order)?;
+        Ok(Transform { columns, ..Transform::identity(box_shape) })
+    }
+
+    /// Returns the transform which moves the rows within one band around. Row i of the band in the
+    /// new sudoku is row `order[i]` of the band in the old one.
+    ///
+    /// # Arguments
+    /// * `box_shape` - The shape of the subgrids of the sudokus the transform is for
+    /// * `band` - The band, counting from 0 at the top
+    /// * `order` - For every row of the band, the row of the band it comes from
+    pub fn permute_rows_in_band(box_shape: BoxShape, band: usize, order: &[usize]) -> Result<Transform, TransformError> {
+        let rows = permute_within_block(box_shape.size(), box_shape.rows, band, order)?;
+        Ok(Transform { rows, ..Transform::identity(box_shape) })
+    }
+
+    /// Returns the transform which moves the columns within one stack around. Column i of the stack
+    /// in the new sudoku is column `order[i]` of the stack in the old one.
+    ///
+    /// # Arguments
+    /// * `box_shape` - The shape of the subgrids of the sudokus the transform is for
+    /// * `stack` - The stack, counting from 0 on the left
+    /// * `order` - For every column of the stack, the column of the stack it comes from
+    pub fn permute_columns_in_stack(box_shape: BoxShape, stack: usize, order: &[usize]) -> Result<Transform, TransformError> {
+        let columns = permute_within_block(box_shape.size(), box_shape.columns, stack, order)?;
+        Ok(Transform { columns, ..Transform::identity(box_shape) })
+    }
+
+    /// Returns the transform which swaps the rows and the columns
+    ///
+    /// # Arguments
+    /// * `box_shape` - The shape of the subgrids of the sudokus the transform is for
+    pub fn transpose(box_shape: BoxShape) -> Transform {
+        Transform { transpose: true, ..Transform::identity(box_shape) }
+    }
+
+    /// Returns the transform which turns the sudoku clockwise by a number of quarter turns
+    ///
+    /// # Arguments
+    /// * `box_shape` - The shape of the subgrids of the sudokus the transform is for
+    /// * `quarter_turns` - The number of quarter turns, so 1 for 90 degrees, 2 for 180 degrees and
+    ///   3 for 270 degrees
+    pub fn rotate(box_shape: BoxShape, quarter_turns: u32) -> Transform {
+        let identity = Transform::identity(box_shape);
+        let reversed: Vec<usize> = identity.rows.iter().rev().copied().collect();
+        match quarter_turns % 4 {
+            0 => identity,
+            1 => Transform { transpose: true, rows: reversed, ..identity },
+            2 => Transform { rows: reversed.clone(), columns: reversed, ..identity },
+            _ => Transform { transpose: true, columns: reversed, ..identity },
+        }
+    }
+
+    /// Returns the transform which mirrors the sudoku
+    ///
+    /// # Arguments
+    /// * `box_shape` - The shape of the subgrids of the sudokus the transform is for
+    /// * `reflection` - The line to mirror in
+    pub fn reflect(box_shape: BoxShape, reflection: Reflection) -> Transform {
+        let identity = Transform::identity(box_shape);
+        let reversed: Vec<usize> = identity.rows.iter().rev().copied().collect();
+        match reflection {
+            Reflection::LeftRight => Transform { columns: reversed, ..identity },
+            Reflection::TopBottom => Transform { rows: reversed, ..identity },
+            Reflection::MainDiagonal => Transform { transpose: true, ..identity },
+            Reflection::AntiDiagonal => Transform { transpose: true, rows: reversed.clone(), columns: reversed, ..identity },
+        }
+    }
+
+    /// Returns a random transform, where every transform that keeps the shape of the subgrids is
+    /// equally likely. Sudokus with rectangular subgrids are never transposed, as that would turn
+    /// their subgrids on their side.
+    ///
+    /// # Arguments
+    /// * `box_shape` - The shape of the subgrids of the sudokus the transform is for
+    /// * `rng` - The random number generator to draw from, for example `seeded_rng(seed)`
+    pub fn random(box_shape: BoxShape, rng: &mut impl Rng) -> Transform {
+        let mut digits: Vec<Digit> = (1..=box_shape.size() as Digit).collect();
+        digits.shuffle(rng);
+
+        Transform { digits, ..Transform::random_moves(box_shape, rng) }
+    }
+
+    /// Returns a random transform like `random`, but one which only moves the rows and columns
+    /// around (and transposes), and keeps the numbers as they are. This is enough where it only
+    /// matters which fields hold which numbers together, like for a mask of givens.
+    ///
+    /// # Arguments
+    /// * `box_shape` - The shape of the subgrids of the sudokus the transform is for
+    /// * `rng` - The random number generator to draw from, for example `seeded_rng(seed)`
+    pub(crate) fn random_moves(box_shape: BoxShape, rng: &mut impl Rng) -> Transform {
+        Transform {
+            transpose: box_shape.is_square() && rng.gen_bool(0.5),
+            rows: random_block_permutation(box_shape.size(), box_shape.rows, rng),
+            columns: random_block_permutation(box_shape.size(), box_shape.columns, rng),
+            ..Transform::identity(box_shape)
+        }
+    }
+
+    /// Returns the transform made of the given orders, which the caller has made sure are
+    /// permutations of the right length
+    ///
+    /// # Arguments
+    /// * `box_shape` - The shape of the subgrids of the sudokus the transform is for
+    /// * `rows` - For every row, the row of the original sudoku it comes from
+    /// * `columns` - For every column, the column of the original sudoku it comes from
+    /// * `digits` - For every number (minus one), the number it is replaced with
+    pub(crate) fn from_orders(box_shape: BoxShape, rows: Vec<usize>, columns: Vec<usize>, digits: Vec<Digit>) -> Transform {
+        Transform { box_shape, transpose: false, rows, columns, digits }
+    }
+
+    /// The shape of the subgrids of the sudokus this transform is for
+    pub fn box_shape(&self) -> BoxShape {
+        self.box_shape
+    }
+
+    /// The shape of the subgrids of the sudokus this transform gives
+    pub fn output_box_shape(&self) -> BoxShape {
+        if self.transpose { self.box_shape.rotated() } else { self.box_shape }
+    }
+
+    /// Whether the transform swaps the rows and the columns
+    pub fn is_transposing(&self) -> bool {
+        self.transpose
+    }
+
+    /// For every row, the row of the original sudoku it comes from (before transposing)
+    pub fn row_order(&self) -> &[usize] {
+        &self.rows
+    }
+
+    /// For every column, the column of the original sudoku it comes from (before transposing)
+    pub fn column_order(&self) -> &[usize] {
+        &self.columns
+    }
+
+    /// For every number (minus one), the number it is replaced with
+    pub fn digit_mapping(&self) -> &[Digit] {
+        &self.digits
+    }
+
+    /// Returns the transform which first does this transform, and then the other one
+    ///
+    /// # Arguments
+    /// * `other` - The transform to do second
+    ///
+    /// # Panics
+    ///
+    /// Panics if the other transform isn't made for the sudokus this transform gives.
+    pub fn then(&self, other: &Transform) -> Transform {
+        assert_eq!(other.box_shape, self.output_box_shape(), "the second transform is for sudokus of another shape");
+
+        // the other transform picks rows and columns of our output, which are our columns and
+        // rows when we transpose
+        let (other_rows, other_columns) = if self.transpose {
+            (&other.columns, &other.rows)
+        } else {
+            (&other.rows, &other.columns)
+        };
+        Transform {
+            box_shape: self.box_shape,
+            transpose: self.transpose != other.transpose,
+            rows: other_rows.iter().map(|&row| self.rows[row]).collect(),
+            columns: other_columns.iter().map(|&column| self.columns[column]).collect(),
+            digits: self.digits.iter().map(|&digit| other.digits[digit as usize - 1]).collect(),
+        }
+    }
+
+    /// Returns the transform which undoes this transform
+    pub fn inverse(&self) -> Transform {
+        let (rows, columns) = if self.transpose {
+            (invert(&self.columns), invert(&self.rows))
+        } else {
+            (invert(&self.rows), invert(&self.columns))
+        };
+        let mut digits = vec![0; self.digits.len()];
+        for (index, &digit) in self.digits.iter().enumerate() {
+            digits[digit as usize - 1] = index as Digit + 1;
+        }
+
+        Transform { box_shape: self.output_box_shape(), transpose: self.transpose, rows, columns, digits }
+    }
+
+    /// Returns the transformed sudoku
+    ///
+    /// # Arguments
+    /// * `sudoku` - A reference to the Sudoku grid.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the sudoku doesn't have the shape of subgrids the transform is made for.
+    pub fn apply(&self, sudoku: &Grid) -> Grid {
+        assert_eq!(sudoku.box_shape(), self.box_shape, "the transform is for sudokus of another shape");
+
+        let mut transformed = Grid::empty(self.output_box_shape());
+        for row in 0..sudoku.size() {
+            for column in 0..sudoku.size() {
+                let (from_row, from_column) = if self.transpose {
+                    (self.rows[column], self.columns[row])
+                } else {
+                    (self.rows[row], self.columns[column])
+                };
+                let value = sudoku.get(from_row, from_column).map(|digit| self.digits[digit as usize - 1]);
+                transformed.set(row, column, value);
+            }
+        }
+        transformed
+    }
+
+    /// Returns the transformed puzzle, where the sudoku and its solution are transformed alike.
+    /// The transformed sudoku is graded again (keeping the old grade if it is too large to grade),
+    /// and keeps the seed of the original.
+    ///
+    /// # Arguments
+    /// * `puzzle` - The puzzle to transform
+    ///
+    /// # Panics
+    ///
+    /// Panics if the puzzle doesn't have the shape of subgrids the transform is made for.
+    pub fn apply_to_puzzle(&self, puzzle: &Puzzle) -> Puzzle {
+        let sudoku = self.apply(&puzzle.sudoku);
+        Puzzle {
+            grade: grade_sudoku(&sudoku).unwrap_or_else(|_| puzzle.grade.clone()),
+            sudoku,
+            solution: self.apply(&puzzle.solution),
+            seed: puzzle.seed,
+        }
+    }
+}
+
+/// Returns True if the list holds every number from 0 to length - 1 exactly once
+///
+/// # Arguments
+/// * `list` - The list to check
+/// * `length` - The length the list should have
+fn is_permutation(list: &[usize], length: usize) -> bool {
+    let mut seen = vec![false; length];
+    list.len() == length && list.iter().all(|&item| item < length && !std::mem::replace(&mut seen[item], true))
+}
+
+/// Returns the permutation which undoes the given permutation
+///
+/// # Arguments
+/// * `permutation` - For every position, the position it comes from
+fn invert(permutation: &[usize]) -> Vec<usize> {
+    let mut inverse = vec![0; permutation.len()];
+    for (index, &item) in permutation.iter().enumerate() {
+        inverse[item] = index;
+    }
+    inverse
+}
+
+/// Returns the order of the rows (or columns) when whole blocks of them are moved around
+///
+/// # Arguments
+/// * `size` - The width of the sudoku
+/// * `block_size` - The number of rows in a band (or columns in a stack)
+/// * `order` - For every block, the block it comes from
+fn permute_blocks(size: usize, block_size: usize, order: &[usize]) -> Result<Vec<usize>, TransformError> {
+    let blocks = size / block_size;
+    if !is_permutation(order, blocks) {
+        return Err(TransformError::NotAPermutation { start: 0, end: blocks - 1 });
+    }
+    Ok((0..size).map(|line| order[line / block_size] * block_size + line % block_size).collect())
+}
+
+/// Returns the order of the rows (or columns) when the rows within one band are moved around
+///
+/// # Arguments
+/// * `size` - The width of the sudoku
+/// * `block_size` - The number of rows in a band (or columns in a stack)
+/// * `block` - The band (or stack) in which the rows are moved
+/// * `order` - For every row of the band, the row of the band it comes from
+fn permute_within_block(size: usize, block_size: usize, block: usize, order: &[usize]) -> Result<Vec<usize>, TransformError> {
+    let blocks = size / block_size;
+    if block >= blocks {
+        return Err(TransformError::NoSuchBlock { block, blocks });
+    }
+    if !is_permutation(order, block_size) {
+        return Err(TransformError::NotAPermutation { start: 0, end: block_size - 1 });
+    }
+    let mut lines: Vec<usize> = (0..size).collect();
+    for (index, &from) in order.iter().enumerate() {
+        lines[block * block_size + index] = block * block_size + from;
+    }
+    Ok(lines)
+}
+
+/// Returns a random order of the rows (or columns), which moves the bands around and the rows
+/// within every band
+///
+/// # Arguments
+/// * `size` - The width of the sudoku
+/// * `block_size` - The number of rows in a band (or columns in a stack)
+/// * `rng` - The random number generator to draw from
+fn random_block_permutation(size: usize, block_size: usize, rng: &mut impl Rng) -> Vec<usize> {
+    let mut blocks: Vec<usize> = (0..size / block_size).collect();
+    blocks.shuffle(rng);
+
+    let mut lines = vec![];
+    for block in blocks {
+        let mut within: Vec<usize> = (0..block_size).collect();
+        within.shuffle(rng);
+        lines.extend(within.into_iter().map(|line| block * block_size + line));
+    }
+    lines
+}
@@ -0,0 +1,75 @@
+/*
+    Canonical : Sudokus that are the same up to symmetry, filled in or not and with square or
+    rectangular subgrids, have the same canonical form, and the transform between them is found
+ */
+
+use sudoku_creator::{are_equivalent, canonical_form, canonicalize, parse_sudoku, seeded_rng, BoxShape, Transform};
+
+
+/// A 9x9 sudoku to solve, with a unique solution
+const PUZZLE_9X9: &str = "53..7....6..195....98....6.8...6...34..8.3..17...2...6.6....28....419..5....8..79";
+
+/// A 6x6 sudoku to solve with subgrids of 2 rows and 3 columns, with a unique solution
+const PUZZLE_6X6: &str = ".6.5.441....1.2.6...413....34...1...";
+
+#[test]
+fn shuffled_puzzle_is_equivalent() {
+    let sudoku = parse_sudoku(PUZZLE_9X9, None).unwrap();
+    let mut rng = seeded_rng(3);
+    for _ in 0..5 {
+        let shuffled = Transform::random(sudoku.box_shape(), &mut rng).apply(&sudoku);
+        let transform = are_equivalent(&sudoku, &shuffled).unwrap();
+        assert_eq!(transform.apply(&sudoku), shuffled);
+        assert_eq!(canonical_form(&sudoku), canonical_form(&shuffled));
+    }
+}
+
+#[test]
+fn canonical_form_is_its_own_canonical_form() {
+    for (puzzle, box_shape) in [(PUZZLE_9X9, BoxShape::new(3, 3)), (PUZZLE_6X6, BoxShape::new(2, 3))] {
+        let sudoku = parse_sudoku(puzzle, Some(box_shape)).unwrap();
+        let (form, transform) = canonicalize(&sudoku);
+        assert_eq!(transform.apply(&sudoku), form);
+        assert_eq!(canonical_form(&form), form);
+    }
+}
+
+#[test]
+fn puzzles_with_different_givens_are_not_equivalent() {
+    let sudoku = parse_sudoku(PUZZLE_9X9, None).unwrap();
+    let mut fewer = sudoku.clone();
+    fewer.set(0, 0, None);
+    assert_eq!(are_equivalent(&sudoku, &fewer), None);
+
+    // the same number of givens, but one of them moved to another empty field of its row
+    let mut moved = fewer.clone();
+    moved.set(0, 2, Some(5));
+    assert_eq!(are_equivalent(&sudoku, &moved), None);
+}
+
+#[test]
+fn transposed_rectangular_puzzle_is_equivalent() {
+    let wide = parse_sudoku(PUZZLE_6X6, Some(BoxShape::new(2, 3))).unwrap();
+    let high = Transform::transpose(wide.box_shape()).apply(&wide);
+    assert_eq!(high.box_shape(), BoxShape::new(3, 2));
+
+    let transform = are_equivalent(&wide, &high).unwrap();
+    assert_eq!(transform.apply(&wide), high);
+    let transform = are_equivalent(&high, &wide).unwrap();
+    assert_eq!(transform.apply(&high), wide);
+    assert_eq!(canonical_form(&wide), canonical_form(&high));
+}
+
+#[test]
+fn relabelled_rectangular_puzzle_with_swapped_rows_is_equivalent() {
+    let sudoku = parse_sudoku(PUZZLE_6X6, Some(BoxShape::new(2, 3))).unwrap();
+    let box_shape = sudoku.box_shape();
+    let relabel = Transform::relabel(box_shape, &[4, 6, 1, 2, 5, 3]).unwrap();
+    let swap_rows = Transform::permute_rows_in_band(box_shape, 1, &[1, 0]).unwrap();
+    let copy = relabel.then(&swap_rows).apply(&sudoku);
+    assert_ne!(copy, sudoku);
+
+    let transform = are_equivalent(&sudoku, &copy).unwrap();
+    assert_eq!(transform.apply(&sudoku), copy);
+    assert_eq!(canonical_form(&sudoku), canonical_form(&copy));
+}
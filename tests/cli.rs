@@ -0,0 +1,98 @@
+/*
+    Cli : Runs the command line tool the way a shell script would, and checks what it writes and
+    the exit codes it returns.
+ */
+
+use std::io::{ErrorKind, Write};
+use std::process::{Command, Output, Stdio};
+
+
+/// The Wikipedia sudoku, which has exactly one solution
+const SUDOKU: &str = "53..7....6..195....98....6.8...6...34..8.3..17...2...6.6....28....419..5....8..79";
+
+/// Runs the command line tool with the arguments, and the input on its standard input
+///
+/// # Arguments
+/// * `arguments` - The arguments, starting with the command
+/// * `input` - What to write to the standard input
+fn run(arguments: &[&str], input: &str) -> Output {
+    let mut child = Command::new(env!("CARGO_BIN_EXE_sudoku_creator"))
+        .args(arguments)
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .spawn()
+        .unwrap();
+    // a command that stops at its arguments exits without reading its input
+    if let Err(error) = child.stdin.take().unwrap().write_all(input.as_bytes()) {
+        assert_eq!(error.kind(), ErrorKind::BrokenPipe, "{}", error);
+    }
+    child.wait_with_output().unwrap()
+}
+
+/// Returns the standard output as lines
+fn lines(output: &Output) -> Vec<String> {
+    String::from_utf8_lossy(&output.stdout).lines().map(|line| line.to_string()).collect()
+}
+
+#[test]
+fn generated_sudokus_are_solved_and_valid() {
+    let generated = run(&["generate", "--count", "3", "--seed", "7", "--symmetry", "half-turn", "--solution"], "");
+    assert_eq!(generated.status.code(), Some(0));
+    let generated = lines(&generated);
+    assert_eq!(generated.len(), 3);
+    for (index, line) in generated.iter().enumerate() {
+        assert!(line.ends_with(&format!(" # seed {}", 7 + index)), "{}", line);
+    }
+
+    let input = generated.join("\n");
+    let validated = run(&["validate"], &input);
+    assert_eq!(validated.status.code(), Some(0));
+    assert!(lines(&validated).iter().all(|line| line.ends_with("valid")));
+
+    // the solution is written after the sudoku, on the same line
+    let solved = run(&["solve", "-"], &input);
+    assert_eq!(solved.status.code(), Some(0));
+    let solutions: Vec<&str> = generated.iter().map(|line| line.split(' ').nth(1).unwrap()).collect();
+    assert_eq!(lines(&solved), solutions);
+}
+
+#[test]
+fn same_seed_gives_the_same_sudokus() {
+    let arguments = ["generate", "--size", "6", "--count", "2", "--seed", "3"];
+    assert_eq!(lines(&run(&arguments, "")), lines(&run(&arguments, "")));
+}
+
+#[test]
+fn transformed_sudoku_has_the_same_canonical_form() {
+    let transformed = run(&["transform", "--rotate", "1", "--relabel", "219876543", "--seed", "5"], SUDOKU);
+    assert_eq!(transformed.status.code(), Some(0));
+    let transformed = lines(&transformed).join("\n");
+    assert_ne!(transformed, SUDOKU);
+    assert_eq!(lines(&run(&["canonicalize"], &transformed)), lines(&run(&["canonicalize"], SUDOKU)));
+}
+
+#[test]
+fn failures_give_exit_codes_and_messages() {
+    // a sudoku without a solution, and a line that isn't a sudoku
+    let input = format!("{}\n{}\nnot a sudoku\n", SUDOKU, SUDOKU.replacen('.', "3", 1));
+    let solved = run(&["solve"], &input);
+    assert_eq!(solved.status.code(), Some(1));
+    assert_eq!(lines(&solved).len(), 1);
+    let errors = String::from_utf8_lossy(&solved.stderr);
+    assert!(errors.contains("line 2:") && errors.contains("line 3:"), "{}", errors);
+
+    assert_eq!(run(&["solve", "--backend", "magic"], "").status.code(), Some(2));
+    assert_eq!(run(&["unknown"], "").status.code(), Some(2));
+    // grade and validate don't write sudokus, so they have no format
+    assert_eq!(run(&["grade", "--format", "grid"], SUDOKU).status.code(), Some(2));
+    assert_eq!(run(&["validate", "--format", "grid"], SUDOKU).status.code(), Some(2));
+    assert_eq!(run(&[], "").status.code(), Some(2));
+    assert_eq!(run(&["grade", "/no/such/file"], "").status.code(), Some(3));
+
+    // finding the canonical form of a 16x16 sudoku would take far too long
+    let large = run(&["canonicalize"], &".".repeat(256));
+    assert_eq!(large.status.code(), Some(2));
+    assert!(String::from_utf8_lossy(&large.stderr).contains("up to 12x12, not 16x16"));
+    assert_eq!(run(&["canonicalize", "--box", "4x4"], "").status.code(), Some(2));
+}
@@ -0,0 +1,55 @@
+/*
+    Daily : The seed and the puzzle of the day may never change between versions, as every client
+    asking for the same day has to get the same sudoku. A change to the hash or to the generator
+    makes these golden values fail, and then the change has to be released as a breaking one.
+ */
+
+use sudoku_creator::{daily_seed, format_sudoku_line, generate_daily_puzzle, Date, Difficulty};
+
+
+/// The puzzles of the 19th of January 2024 for the namespace "my-app", with their solutions
+const GOLDEN_PUZZLES: [(Difficulty, &str, &str); 5] = [
+    (
+        Difficulty::Easy,
+        "...58.........24...9....15....6.4....14.9....9.8.1....8......1.17..6...3.23....64",
+        "461589372785132496392746158237654981614897235958213647846325719179468523523971864",
+    ),
+    (
+        Difficulty::Medium,
+        ".84...1...9..7..4....46.5...2.713.....1......6.9..5........13...18.2.9.5....9....",
+        "284539167596178243173462589825713496731946852649285731967851324318624975452397618",
+    ),
+    (
+        Difficulty::Hard,
+        "..3..948.7.93...6....5......2.7.8...93.1....21.4.95.........67...6....9.....5..1.",
+        "253619487749382561861547923625738149937164852184295736398421675516873294472956318",
+    ),
+    (
+        Difficulty::Expert,
+        "..51....8..1..2..487...4.5....9...3.....156...5....9.....73..42....5...33....1..6",
+        "435197268691582374872364159126978435943215687758643921519736842264859713387421596",
+    ),
+    (
+        Difficulty::Diabolical,
+        ".9...8......2...1....6.5.....9.5.3...7.1..8..23....4....6.3...8.1..89.....45...9.",
+        "691348725853297614742615983169854372475123869238976451926731548517489236384562197",
+    ),
+];
+
+#[test]
+fn daily_seed_never_changes() {
+    let date = Date::new(2024, 1, 19).unwrap();
+    assert_eq!(daily_seed(date, "my-app", Difficulty::Hard), 8277976699794504055);
+}
+
+#[test]
+fn daily_puzzle_never_changes() {
+    let date: Date = "2024-01-19".parse().unwrap();
+    for (difficulty, sudoku, solution) in GOLDEN_PUZZLES {
+        let puzzle = generate_daily_puzzle(date, "my-app", difficulty).unwrap();
+        assert_eq!(puzzle.seed, daily_seed(date, "my-app", difficulty), "{}", difficulty);
+        assert_eq!(puzzle.grade.difficulty, difficulty);
+        assert_eq!(format_sudoku_line(&puzzle.sudoku), sudoku, "{}", difficulty);
+        assert_eq!(format_sudoku_line(&puzzle.solution), solution, "{}", difficulty);
+    }
+}
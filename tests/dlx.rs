@@ -0,0 +1,57 @@
+/*
+    Dlx : Sets of rules that don't cover every field of the sudoku, which the exact cover search
+    has to handle without complaining, and without putting two numbers in one field.
+ */
+
+use sudoku_creator::dlx::{solutions_with_rules, Diagonals};
+use sudoku_creator::{BoxShape, Digit, Grid};
+
+
+#[test]
+fn diagonals_alone_leave_the_other_fields_empty() {
+    let sudoku = Grid::empty(BoxShape::new(2, 2));
+    let solutions: Vec<Grid> = solutions_with_rules(&sudoku, &[&Diagonals]).collect();
+
+    // the 4 numbers can go on each of the two diagonals (which don't cross) in 4! ways
+    assert_eq!(solutions.len(), 24 * 24);
+    for solution in solutions {
+        let main: Vec<Option<Digit>> = (0..4).map(|index| solution.get(index, index)).collect();
+        let anti: Vec<Option<Digit>> = (0..4).map(|index| solution.get(index, 3 - index)).collect();
+        for diagonal in [main, anti] {
+            let mut numbers: Vec<Digit> = diagonal.iter().map(|value| value.expect("every diagonal field is filled in")).collect();
+            numbers.sort_unstable();
+            assert_eq!(numbers, vec![1, 2, 3, 4], "{:?}", diagonal);
+        }
+        for (row, column, value) in solution.fields() {
+            if row != column && row + column != 3 {
+                assert_eq!(value, None, "field ({}, {})", row, column);
+            }
+        }
+    }
+}
+
+#[test]
+fn a_field_gets_one_number_without_the_cell_rules() {
+    // on a 3x3 sudoku (with 1x3 subgrids) the diagonals cross in the middle, so the middle field
+    // must hold a number that both diagonals can use
+    let sudoku = Grid::empty(BoxShape::new(1, 3));
+    for solution in solutions_with_rules(&sudoku, &[&Diagonals]) {
+        let main: Vec<Digit> = (0..3).map(|index| solution.get(index, index).unwrap()).collect();
+        let anti: Vec<Digit> = (0..3).map(|index| solution.get(index, 2 - index).unwrap()).collect();
+        for mut numbers in [main, anti] {
+            numbers.sort_unstable();
+            assert_eq!(numbers, vec![1, 2, 3]);
+        }
+    }
+    // 3 numbers for the middle, then 2 ways for the rest of each diagonal
+    assert_eq!(solutions_with_rules(&sudoku, &[&Diagonals]).count(), 3 * 2 * 2);
+}
+
+#[test]
+fn without_rules_the_sudoku_is_its_only_solution() {
+    let mut sudoku = Grid::empty(BoxShape::new(2, 2));
+    sudoku.set(0, 0, Some(1));
+    let solutions: Vec<Grid> = solutions_with_rules(&sudoku, &[]).collect();
+
+    assert_eq!(solutions, vec![sudoku]);
+}
@@ -0,0 +1,239 @@
+/*
+    Generator : Sudokus generated to a requirement have to meet it, removing fields has to stop,
+    also when the requested number of fields can't be emptied, minimal sudokus need every given
+    number, limits on the givens hold, the givens keep their symmetry, and the givens follow the
+    mask they were drawn with
+ */
+
+use std::time::{Duration, Instant};
+
+use sudoku_creator::grading::technique_rating;
+use sudoku_creator::{
+    generate_full_sudoku_with_box_shape, generate_puzzle_from_mask, generate_puzzle_requiring, generate_puzzle_with_limits, generate_sudoku_to_solve,
+    generate_sudoku_to_solve_with, get_solution, has_unique_solution, is_minimal, minimise, seeded_rng, solve_logically_with,
+    BoxShape, GenerationBudget, GenerationTarget, GivenLimits, GivenRange, Grid, Reflection, RemovalEnd, RemovalMode,
+    RemovalOptions, Symmetry, Technique,
+};
+
+
+/// Returns True if the logical solver fills in the sudoku with only these techniques
+///
+/// # Arguments
+/// * `sudoku` - A reference to the Sudoku grid.
+/// * `techniques` - The techniques the solver may use
+fn is_solved_with(sudoku: &Grid, techniques: &[Technique]) -> bool {
+    solve_logically_with(sudoku, techniques).unwrap().solved
+}
+
+#[test]
+fn required_technique_is_needed() {
+    for (required, seed) in [(Technique::HiddenPair, 36), (Technique::XYWing, 10)] {
+        let budget = GenerationBudget { max_attempts: 20, max_duration: None };
+        let puzzle = generate_puzzle_requiring(required, &[], BoxShape::new(3, 3), budget, seed).unwrap();
+
+        // every technique rated lower or on the same level, also when it comes after the required one
+        let level = technique_rating(required).floor();
+        let allowed: Vec<Technique> = Technique::ALL.into_iter()
+            .filter(|technique| technique_rating(*technique).floor() <= level)
+            .collect();
+        let without_required: Vec<Technique> = allowed.iter().copied().filter(|technique| *technique != required).collect();
+
+        assert!(has_unique_solution(&puzzle.sudoku), "{}", required);
+        assert!(is_solved_with(&puzzle.sudoku, &allowed), "{}", required);
+        assert!(!is_solved_with(&puzzle.sudoku, &without_required), "{}", required);
+    }
+}
+
+#[test]
+fn removal_stops_when_nothing_is_left_to_remove() {
+    let mut rng = seeded_rng(7);
+    let solution = generate_full_sudoku_with_box_shape(BoxShape::new(3, 3), &mut rng);
+    let report = generate_sudoku_to_solve_with(&solution, 70, &RemovalOptions::default(), &mut rng);
+
+    // no 9x9 sudoku with a unique solution has fewer than 17 given numbers
+    assert!(!report.target_reached());
+    assert_eq!(report.end, RemovalEnd::NothingLeftToRemove);
+    assert!(report.removed < 70);
+    assert_eq!(report.sudoku.empty_fields().len(), report.removed);
+    assert_eq!(report.attempts, 81);
+    assert!(has_unique_solution(&report.sudoku));
+}
+
+#[test]
+fn removal_stops_when_the_attempts_run_out() {
+    let mut rng = seeded_rng(7);
+    let solution = generate_full_sudoku_with_box_shape(BoxShape::new(3, 3), &mut rng);
+    let options = RemovalOptions { max_attempts: Some(5), ..RemovalOptions::default() };
+    let report = generate_sudoku_to_solve_with(&solution, 70, &options, &mut rng);
+
+    assert!(!report.target_reached());
+    assert_eq!(report.end, RemovalEnd::OutOfAttempts);
+    assert_eq!(report.attempts, 5);
+    assert!(report.removed <= 5);
+    assert_eq!(report.sudoku.empty_fields().len(), report.removed);
+}
+
+#[test]
+fn sixteen_by_sixteen_is_generated_in_time() {
+    // every removal is checked for a unique solution, which has to be quick on sparse 16x16 grids
+    let start = Instant::now();
+    let mut rng = seeded_rng(1);
+    let solution = generate_full_sudoku_with_box_shape(BoxShape::new(4, 4), &mut rng);
+    let report = generate_sudoku_to_solve_with(&solution, 160, &RemovalOptions::default(), &mut rng);
+
+    assert!(start.elapsed() < Duration::from_secs(30), "took {:?}", start.elapsed());
+    assert!(report.target_reached());
+    assert!(has_unique_solution(&report.sudoku));
+}
+
+#[test]
+fn minimised_sudoku_is_minimal() {
+    for seed in 0..3 {
+        let mut rng = seeded_rng(seed);
+        let solution = generate_full_sudoku_with_box_shape(BoxShape::new(3, 3), &mut rng);
+        let sudoku = generate_sudoku_to_solve(&solution, 40, RemovalMode::Unique, &mut rng);
+        let minimal = minimise(&sudoku).unwrap();
+
+        assert!(is_minimal(&minimal), "seed {}", seed);
+        assert!(has_unique_solution(&minimal), "seed {}", seed);
+        assert_eq!(get_solution(&minimal), Ok(solution.clone()), "seed {}", seed);
+        // only given numbers of the original are kept, and they are not changed
+        for (row, column, value) in minimal.fields().filter(|(_, _, value)| value.is_some()) {
+            assert_eq!(sudoku.get(row, column), value, "seed {}", seed);
+        }
+    }
+}
+
+#[test]
+fn sudoku_with_more_solutions_is_not_minimal() {
+    let sudoku = Grid::empty(BoxShape::new(3, 3));
+    assert!(!is_minimal(&sudoku));
+    assert_eq!(minimise(&sudoku), None);
+}
+
+/// Limits of 2 to 4 given numbers in every row, column, subgrid and of every number, and 24 to 30
+/// in total, which one pass of removing fields often misses
+fn tight_limits() -> GivenLimits {
+    let range = GivenRange::new(2, 4);
+    GivenLimits { per_row: range, per_column: range, per_box: range, per_digit: range, total: GivenRange::new(24, 30) }
+}
+
+#[test]
+fn generated_sudoku_keeps_the_limits() {
+    let options = RemovalOptions { limits: tight_limits(), ..RemovalOptions::default() };
+    for seed in 0..6 {
+        let puzzle = generate_puzzle_with_limits(81, &options, BoxShape::new(3, 3), GenerationBudget::default(), seed).unwrap();
+        let sudoku = &puzzle.sudoku;
+
+        assert!(tight_limits().allows(sudoku), "seed {}", seed);
+        assert!(has_unique_solution(sudoku), "seed {}", seed);
+        assert_eq!(get_solution(sudoku), Ok(puzzle.solution.clone()), "seed {}", seed);
+        for row in 0..9 {
+            let givens = (0..9).filter(|&column| sudoku.get(row, column).is_some()).count();
+            assert!((2..=4).contains(&givens), "seed {}, row {} has {} givens", seed, row, givens);
+        }
+        for number in 1..=9 {
+            let givens = sudoku.fields().filter(|&(_, _, value)| value == Some(number)).count();
+            assert!((2..=4).contains(&givens), "seed {}, {} is given {} times", seed, number, givens);
+        }
+    }
+}
+
+#[test]
+fn impossible_limits_use_up_the_budget() {
+    // a 9x9 sudoku with a unique solution needs at least 17 given numbers
+    let limits = GivenLimits { total: GivenRange::new(0, 10), ..GivenLimits::default() };
+    let options = RemovalOptions { limits, ..RemovalOptions::default() };
+    let budget = GenerationBudget { max_attempts: 3, max_duration: None };
+    let failure = generate_puzzle_with_limits(81, &options, BoxShape::new(3, 3), budget, 1).unwrap_err();
+
+    assert_eq!(failure.target, GenerationTarget::Limits);
+    assert_eq!(failure.attempts, 3);
+    let closest = failure.closest.unwrap();
+    assert!(!limits.allows(&closest.sudoku));
+    assert!(has_unique_solution(&closest.sudoku));
+}
+
+#[test]
+fn givens_are_symmetric() {
+    let symmetries = [Symmetry::HalfTurn, Symmetry::QuarterTurn, Symmetry::Dihedral, Symmetry::Mirror(Reflection::AntiDiagonal)];
+    for box_shape in [BoxShape::new(3, 3), BoxShape::new(2, 3)] {
+        let size = box_shape.size();
+        for symmetry in symmetries {
+            let mut rng = seeded_rng(11);
+            let solution = generate_full_sudoku_with_box_shape(box_shape, &mut rng);
+            let options = RemovalOptions { symmetry, ..RemovalOptions::default() };
+            let report = generate_sudoku_to_solve_with(&solution, size * size, &options, &mut rng);
+            let sudoku = &report.sudoku;
+            assert!(report.removed > 0, "{:?} on {:?}", symmetry, box_shape);
+            assert!(has_unique_solution(sudoku), "{:?} on {:?}", symmetry, box_shape);
+
+            for (row, column, value) in sudoku.fields() {
+                for (other_row, other_column) in symmetry.orbit(size, row, column) {
+                    assert_eq!(
+                        sudoku.get(other_row, other_column).is_some(), value.is_some(),
+                        "{:?} on {:?}: ({}, {}) and ({}, {})", symmetry, box_shape, row, column, other_row, other_column,
+                    );
+                }
+            }
+
+            // the orbits themselves hold the fields the symmetry maps onto each other
+            let last = size - 1;
+            let image = match symmetry {
+                Symmetry::HalfTurn => (last, last - 1),
+                Symmetry::Mirror(Reflection::AntiDiagonal) => (last - 1, last),
+                _ => (1, last),
+            };
+            assert!(symmetry.orbit(size, 0, 1).contains(&image), "{:?}", symmetry);
+        }
+    }
+}
+
+/// Returns the mask with True for every field the check holds for
+///
+/// # Arguments
+/// * `size` - The width and height of the sudoku
+/// * `given` - Returns True if the field, by row and column, should be a given
+fn mask_of(size: usize, given: impl Fn(usize, usize) -> bool) -> Vec<Vec<bool>> {
+    (0..size).map(|row| (0..size).map(|column| given(row, column)).collect()).collect()
+}
+
+#[test]
+fn givens_match_the_mask() {
+    let masks = [
+        (BoxShape::new(3, 3), mask_of(9, |row, column| (row + column) % 2 == 0)),
+        (BoxShape::new(3, 3), mask_of(9, |row, column| row.abs_diff(4) + column.abs_diff(4) >= 3)),
+        (BoxShape::new(2, 3), mask_of(6, |row, column| (row + 2 * column) % 3 != 0)),
+    ];
+    for (box_shape, mask) in masks {
+        let budget = GenerationBudget { max_attempts: 50, max_duration: None };
+        let puzzle = generate_puzzle_from_mask(&mask, box_shape, budget, 3).unwrap();
+        let sudoku = &puzzle.sudoku;
+
+        assert!(has_unique_solution(sudoku), "{:?}", mask);
+        assert_eq!(get_solution(sudoku), Ok(puzzle.solution.clone()), "{:?}", mask);
+        for (row, column, value) in sudoku.fields() {
+            assert_eq!(value.is_some(), mask[row][column], "{:?}: ({}, {})", mask, row, column);
+        }
+    }
+}
+
+#[test]
+fn mask_without_a_unique_solution_fails_right_away() {
+    let masks = [
+        // rows 0 and 1 are empty, so they can be swapped in any solution
+        mask_of(9, |row, _| row >= 2),
+        // the first two stacks are empty, so they can be swapped in any solution
+        mask_of(9, |_, column| column >= 6),
+        // 7 givens leave two numbers that are never given, which can be swapped
+        mask_of(9, |row, column| row == column && row < 7),
+    ];
+    for mask in masks {
+        let givens = mask.iter().flatten().filter(|&&given| given).count();
+        let failure = generate_puzzle_from_mask(&mask, BoxShape::new(3, 3), GenerationBudget::default(), 3).unwrap_err();
+
+        assert_eq!(failure.target, GenerationTarget::Mask { givens });
+        assert_eq!(failure.attempts, 0, "{:?}", mask);
+        assert!(failure.closest.is_none(), "{:?}", mask);
+    }
+}
@@ -0,0 +1,32 @@
+/*
+    Grid : Fields outside of the sudoku, and sudokus too large to hold, are rejected instead of
+    being read or written somewhere else.
+ */
+
+use sudoku_creator::{parse_sudoku, BoxShape, Grid, GridError, ParseSudokuError};
+
+
+#[test]
+#[should_panic(expected = "there is no field (0, 9)")]
+fn reading_past_the_last_column_panics() {
+    Grid::empty(BoxShape::new(3, 3)).get(0, 9);
+}
+
+#[test]
+#[should_panic(expected = "there is no field (9, 0)")]
+fn writing_past_the_last_row_panics() {
+    Grid::empty(BoxShape::new(3, 3)).set(9, 0, Some(1));
+}
+
+#[test]
+fn too_large_sudoku_is_rejected() {
+    let rows = vec![vec![0; 256]; 256];
+    assert_eq!(Grid::from_rows(&rows, BoxShape::new(16, 16)), Err(GridError::TooLarge { size: 256, max: 255 }));
+}
+
+#[test]
+fn too_large_line_is_rejected() {
+    let line = ".".repeat(256 * 256);
+    assert_eq!(parse_sudoku(&line, None), Err(ParseSudokuError::TooLarge { size: 256, max: 35 }));
+    assert_eq!(parse_sudoku(&".".repeat(36 * 36), None), Err(ParseSudokuError::TooLarge { size: 36, max: 35 }));
+}
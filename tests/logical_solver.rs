@@ -0,0 +1,105 @@
+/*
+    Logical solver : For every technique a sudoku in which it applies, together with the numbers
+    its first step fills in and the candidates it removes
+ */
+
+use sudoku_creator::{
+    get_next_step, get_solution, grade_sudoku, parse_sudoku, solve_logically, solve_logically_with, BoxShape, Digit, Grid,
+    LogicalSolveError, Step, Technique,
+};
+
+
+/// A technique, a sudoku in which it applies, and the placements and eliminations of its first
+/// step as (row, column, number)
+type Fixture = (Technique, &'static str, &'static [(usize, usize, Digit)], &'static [(usize, usize, Digit)]);
+
+const FIXTURES: [Fixture; 19] = [
+    (Technique::HiddenSingle, ".19..2..5.....6..4..87...6.......5.27.12........6...3.......9..8..9357...6.......", &[(0, 0, 6)], &[]),
+    (Technique::NakedSingle, "1...2.5.33...5....685143279.5.238...216497835839615427...561......372.5.56.984...", &[(8, 7, 1)], &[]),
+    (Technique::Pointing, "..679...2.94.63......5.8.9...9..72...8...9.53.....596.9328.......8.524.9..59.6.2.", &[], &[(8, 4, 4)]),
+    (Technique::Claiming, "8.2..9.7457.82..39..91.7825..67819533972654181584932677....23..9.531.74...3.7.5..", &[], &[(0, 1, 6)]),
+    (Technique::NakedPair, "2.5.87..3.7.235..8.834.6527....2.4797926543814.87..265....7.15...73.1892...5..73.", &[], &[(8, 4, 9)]),
+    (Technique::XWing, "65...4.8..3.5684.1.....36.51.9.85.4.37421...85..43.1..89.35.214.15..2.362.3..1..9", &[], &[(5, 1, 6)]),
+    (Technique::HiddenPair, "...287319932164587..75934262..73..9447..19235.93....71.6..7.9..7.59.8163..9...7..", &[], &[(5, 0, 8)]),
+    (Technique::NakedTriple, ".4913.25717....38932.7.96145173..896982567143463891725.54..39.17.....4.22.....5.8", &[], &[(7, 2, 6), (7, 5, 6)]),
+    (Technique::Swordfish, "...49.6.57956124386.4.35.91....59.469.6274.5345.36.9.2..3.46..924958..67.6.92...4", &[], &[(3, 2, 7)]),
+    (Technique::HiddenTriple, ".139..768...71853.8..36.19....276945654193827..28..316...439671.4.687253...521489", &[], &[(1, 0, 9)]),
+    (Technique::XYWing, "8.2..9.7457.82..39..9..7825..67819533972654181584932677....23..9.531.74...3.7.5..", &[], &[(2, 1, 1)]),
+    (Technique::XYZWing, "......5186.821.3973.1.98642..3.2718..1.....6.5....1.23..51794367398462511..352879", &[], &[(5, 1, 4)]),
+    (Technique::WWing, "548139276.1.827435237654981..2.95148154.8269.8..416.52.25968.14...54182.481273569", &[], &[(6, 6, 7)]),
+    (Technique::UniqueRectangle, "9386547..7..9..638612837495...26.3.43.674....2..5.39.656347...94.7195.63...3.6547", &[], &[(4, 7, 1), (4, 7, 2)]),
+    (Technique::NakedQuad, "5....27..1...7.3.227983461561.......9....71..75..8.9...276.....8917.32...65......", &[], &[(6, 7, 4), (6, 7, 5), (6, 7, 8), (6, 8, 4), (6, 8, 8), (8, 7, 4), (8, 7, 8), (8, 8, 4), (8, 8, 8)]),
+    (Technique::Jellyfish, "714.863.586.531..453..4.1684.1.598.669842.5..3.561894..56894....87.6.459943.7568.", &[], &[(2, 3, 2), (7, 3, 2), (1, 7, 2), (6, 7, 2), (6, 8, 2)]),
+    (Technique::HiddenQuad, "9385..........6.8..6.39.........1....7.8.263..4.6.5.7.3.6...2.....7....8..1.....6", &[], &[(5, 2, 3)]),
+    (Technique::SimpleColouring, ".9.2183..157634829283597461379465182.6..2193..123.96.4.3.1.27.6.2.9.35.8.4...62.3", &[], &[(8, 0, 8)]),
+    (Technique::Chain, "97..3.....3..81.79.8.5792.3.49.6..3..5..984..81..4.9.736.82479..9..1..24.2.95.3..", &[], &[(1, 2, 6)]),
+];
+
+/// Returns the first step of the logical solver when it may only use one technique
+///
+/// # Arguments
+/// * `sudoku` - A reference to the Sudoku grid.
+/// * `technique` - The only technique the solver may use
+fn first_step(sudoku: &Grid, technique: Technique) -> Option<Step> {
+    solve_logically_with(sudoku, &[technique]).unwrap().steps.into_iter().next()
+}
+
+#[test]
+fn every_technique_has_a_fixture() {
+    let techniques: Vec<Technique> = FIXTURES.iter().map(|fixture| fixture.0).collect();
+    assert_eq!(techniques, Technique::ALL);
+}
+
+#[test]
+fn every_technique_makes_its_step() {
+    for (technique, sudoku, placements, eliminations) in FIXTURES {
+        let sudoku = parse_sudoku(sudoku, None).unwrap();
+        let step = first_step(&sudoku, technique).unwrap_or_else(|| panic!("{} didn't apply", technique));
+        let found_placements: Vec<_> = step.placements.iter().map(|field| (field.row, field.column, field.number)).collect();
+        let found_eliminations: Vec<_> = step.eliminations.iter().map(|field| (field.row, field.column, field.number)).collect();
+        assert_eq!(step.technique, technique);
+        assert_eq!(found_placements, placements, "{}", technique);
+        assert_eq!(found_eliminations, eliminations, "{}", technique);
+    }
+}
+
+#[test]
+fn every_step_agrees_with_the_solution() {
+    for (technique, sudoku, _, _) in FIXTURES {
+        let sudoku = parse_sudoku(sudoku, None).unwrap();
+        let solution = get_solution(&sudoku).unwrap();
+        let step = first_step(&sudoku, technique).unwrap();
+        for field in &step.placements {
+            assert_eq!(solution.get(field.row, field.column), Some(field.number), "{}", technique);
+        }
+        for field in &step.eliminations {
+            assert_ne!(solution.get(field.row, field.column), Some(field.number), "{}", technique);
+        }
+    }
+}
+
+#[test]
+fn hint_uses_the_easiest_technique() {
+    // in the hidden quad fixture an easier technique applies as well
+    for (technique, sudoku, _, _) in FIXTURES.into_iter().filter(|fixture| fixture.0 != Technique::HiddenQuad) {
+        let sudoku = parse_sudoku(sudoku, None).unwrap();
+        assert_eq!(get_next_step(&sudoku).unwrap(), first_step(&sudoku, technique), "{}", technique);
+    }
+}
+
+#[test]
+fn too_large_sudoku_is_rejected() {
+    let sudoku = Grid::empty(BoxShape::new(9, 9));
+    let error = LogicalSolveError::TooLarge { size: 81, max: 64 };
+    assert_eq!(solve_logically(&sudoku).unwrap_err(), error);
+    assert_eq!(get_next_step(&sudoku).unwrap_err(), error);
+    assert_eq!(grade_sudoku(&sudoku).unwrap_err(), error);
+}
+
+#[test]
+fn unsolved_sudoku_has_no_hardest_technique() {
+    let grade = grade_sudoku(&Grid::empty(BoxShape::new(3, 3))).unwrap();
+    assert!(!grade.solved_logically);
+    assert_eq!(grade.hardest_technique, None);
+    assert_eq!(grade.to_string(), "Diabolical (10.0, the techniques alone can't solve it)");
+}
@@ -0,0 +1,40 @@
+/*
+    Random : A seed gives the same sudoku on every platform and in every version, so a sudoku
+    that someone reports can be made again from its seed. A change to the random numbers or to the
+    order in which the generator uses them makes these golden values fail.
+ */
+
+use sudoku_creator::{
+    format_sudoku_line, generate_full_sudoku, generate_graded_sudoku, seeded_rng, BoxShape, Difficulty, RemovalMode,
+};
+
+
+/// Filled in 9x9 sudokus made by `generate_full_sudoku` from these seeds
+const GOLDEN_FULL_SUDOKUS: [(u64, &str); 3] = [
+    (0, "796435281512968473483271965937682514845197326261543897329756148178324659654819732"),
+    (1, "619482375372516894548793261496371582731258649285649137157864923824935716963127458"),
+    (42, "329174685748652193615389427862745931957813246431926578593467812176298354284531769"),
+];
+
+#[test]
+fn full_sudoku_never_changes() {
+    for (seed, golden) in GOLDEN_FULL_SUDOKUS {
+        let sudoku = generate_full_sudoku(9, 9, &mut seeded_rng(seed));
+        assert_eq!(format_sudoku_line(&sudoku), golden, "seed {}", seed);
+    }
+}
+
+#[test]
+fn seeded_puzzle_never_changes() {
+    let puzzle = generate_graded_sudoku(BoxShape::new(3, 3), 50, RemovalMode::Unique, 42).unwrap();
+    assert_eq!(puzzle.seed, 42);
+    assert_eq!(
+        format_sudoku_line(&puzzle.sudoku),
+        "3.9.746..74...2.9..153....7......931......2..43.9......9.4..812...29...42..53....",
+    );
+    assert_eq!(
+        format_sudoku_line(&puzzle.solution),
+        "329174685748652193615389427862745931957813246431926578593467812176298354284531769",
+    );
+    assert_eq!(puzzle.grade.difficulty, Difficulty::Easy);
+}
@@ -0,0 +1,189 @@
+/*
+    Regression : Known sudokus with their known solutions, which every solver backend has to get
+    right. Sudokus are written row by row as text, with a dot for an empty field.
+ */
+
+use std::time::{Duration, Instant};
+
+use sudoku_creator::{
+    count_solutions, count_solutions_with, generate_full_sudoku_with_box_shape, get_solution, get_solution_with, seeded_rng,
+    BoxShape, Digit, Grid, SolveError, SolverBackend,
+};
+
+
+/// Every backend, as each of them has to give the same answers
+const BACKENDS: [SolverBackend; 4] = [SolverBackend::Auto, SolverBackend::Bitmask, SolverBackend::Backtracking, SolverBackend::Dlx];
+
+/// Known sudokus with exactly one solution, as (name, subgrid rows, subgrid columns, sudoku, solution)
+const CORPUS: [(&str, usize, usize, &str, &str); 5] = [
+    (
+        "4x4",
+        2, 2,
+        ".4....2..3....4.",
+        "2431312443121243",
+    ),
+    (
+        "6x6 with 2x3 subgrids",
+        2, 3,
+        ".6.5.441....1.2.6...413....34...1...",
+        "263514415623132465654132526341341256",
+    ),
+    (
+        "Wikipedia, with a given in the top left",
+        3, 3,
+        "53..7....6..195....98....6.8...6...34..8.3..17...2...6.6....28....419..5....8..79",
+        "534678912672195348198342567859761423426853791713924856961537284287419635345286179",
+    ),
+    (
+        "AI Escargot",
+        3, 3,
+        "1....7.9..3..2...8..96..5....53..9...1..8...26....4...3......1..4......7..7...3..",
+        "162857493534129678789643521475312986913586742628794135356478219241935867897261354",
+    ),
+    (
+        "Arto Inkala 2012",
+        3, 3,
+        "8..........36......7..9.2...5...7.......457.....1...3...1....68..85...1..9....4..",
+        "812753649943682175675491283154237896369845721287169534521974368438526917796318452",
+    ),
+];
+
+/// Reads a sudoku written row by row, with a dot for an empty field
+///
+/// # Arguments
+/// * `text` - The fields, as the digits 1 to 9 and dots
+/// * `box_shape` - The shape of the subgrids of the sudoku
+fn parse(text: &str, box_shape: BoxShape) -> Grid {
+    let values: Vec<i32> = text.chars().map(|field| field.to_digit(10).map_or(0, |digit| digit as i32)).collect();
+    let rows: Vec<Vec<i32>> = values.chunks(box_shape.size()).map(|row| row.to_vec()).collect();
+    Grid::from_rows(&rows, box_shape).unwrap()
+}
+
+/// Returns an empty 9x9 sudoku with the given numbers filled in
+///
+/// # Arguments
+/// * `givens` - The fields to fill in, as (row, column, number)
+fn with_givens(givens: &[(usize, usize, Digit)]) -> Grid {
+    let mut sudoku = Grid::empty(BoxShape::new(3, 3));
+    for &(row, column, number) in givens {
+        sudoku.set(row, column, Some(number));
+    }
+    sudoku
+}
+
+#[test]
+fn known_sudokus_are_solved_by_every_backend() {
+    for (name, box_rows, box_columns, sudoku, solution) in CORPUS {
+        let box_shape = BoxShape::new(box_rows, box_columns);
+        let sudoku = parse(sudoku, box_shape);
+        let solution = parse(solution, box_shape);
+        for backend in BACKENDS {
+            assert_eq!(get_solution_with(&sudoku, backend), Ok(solution.clone()), "{} with {:?}", name, backend);
+            assert_eq!(count_solutions_with(&sudoku, 2, backend), 1, "{} with {:?}", name, backend);
+        }
+    }
+}
+
+#[test]
+fn givens_are_never_changed() {
+    for (name, box_rows, box_columns, sudoku, _) in CORPUS {
+        let sudoku = parse(sudoku, BoxShape::new(box_rows, box_columns));
+        for backend in BACKENDS {
+            let solution = get_solution_with(&sudoku, backend).unwrap();
+            for (row, column, value) in sudoku.fields().filter(|(_, _, value)| value.is_some()) {
+                assert_eq!(solution.get(row, column), value, "{} with {:?}", name, backend);
+            }
+        }
+    }
+}
+
+#[test]
+fn filled_in_sudoku_is_its_own_solution() {
+    let (_, box_rows, box_columns, _, solution) = CORPUS[2];
+    let solution = parse(solution, BoxShape::new(box_rows, box_columns));
+    for backend in BACKENDS {
+        assert_eq!(get_solution_with(&solution, backend), Ok(solution.clone()), "{:?}", backend);
+    }
+}
+
+#[test]
+fn empty_sudoku_is_solved() {
+    for box_shape in [BoxShape::new(2, 2), BoxShape::new(2, 3), BoxShape::new(3, 3)] {
+        for backend in BACKENDS {
+            let solution = get_solution_with(&Grid::empty(box_shape), backend).unwrap();
+            assert!(solution.is_solved(), "{:?} with {:?}", box_shape, backend);
+        }
+    }
+}
+
+#[test]
+fn all_solutions_of_an_empty_4x4_are_counted() {
+    for backend in BACKENDS {
+        assert_eq!(count_solutions_with(&Grid::empty(BoxShape::new(2, 2)), 1000, backend), 288, "{:?}", backend);
+    }
+}
+
+#[test]
+fn duplicate_givens_are_reported_up_front() {
+    let sudoku = with_givens(&[(0, 0, 5), (0, 4, 5)]);
+    let error = SolveError::DuplicateGiven { number: 5, first: (0, 0), second: (0, 4) };
+    for backend in BACKENDS {
+        assert_eq!(get_solution_with(&sudoku, backend), Err(error.clone()), "{:?}", backend);
+        assert_eq!(count_solutions_with(&sudoku, 2, backend), 0, "{:?}", backend);
+    }
+}
+
+#[test]
+fn duplicates_in_a_column_and_a_subgrid_are_found() {
+    let in_column = with_givens(&[(0, 0, 7), (8, 0, 7)]);
+    let in_subgrid = with_givens(&[(0, 0, 3), (1, 1, 3)]);
+    for backend in BACKENDS {
+        assert_eq!(
+            get_solution_with(&in_column, backend),
+            Err(SolveError::DuplicateGiven { number: 7, first: (0, 0), second: (8, 0) }),
+        );
+        assert_eq!(
+            get_solution_with(&in_subgrid, backend),
+            Err(SolveError::DuplicateGiven { number: 3, first: (0, 0), second: (1, 1) }),
+        );
+    }
+}
+
+#[test]
+fn sudoku_without_solution_is_reported() {
+    // the last field of the top row can only hold a 9, but there already is one below it
+    let mut givens: Vec<(usize, usize, Digit)> = (0..8).map(|column| (0, column, column as Digit + 1)).collect();
+    givens.push((8, 8, 9));
+    let sudoku = with_givens(&givens);
+    for backend in BACKENDS {
+        assert_eq!(get_solution_with(&sudoku, backend), Err(SolveError::NoSolution), "{:?}", backend);
+        assert_eq!(count_solutions_with(&sudoku, 2, backend), 0, "{:?}", backend);
+    }
+}
+
+#[test]
+fn large_sudokus_are_filled_in() {
+    // 25x25 is filled in field by field, anything wider with the pattern
+    for box_size in [5, 6, 8] {
+        let sudoku = generate_full_sudoku_with_box_shape(BoxShape::new(box_size, box_size), &mut seeded_rng(1));
+        assert!(sudoku.is_solved(), "{}x{}", box_size * box_size, box_size * box_size);
+    }
+}
+
+#[test]
+fn large_sparse_sudokus_are_solved_quickly() {
+    // the default backend searches these with dancing links, where the bitmask solver can take minutes
+    let mut rng = seeded_rng(1);
+    for box_size in [4, 5] {
+        let box_shape = BoxShape::new(box_size, box_size);
+        let solution = generate_full_sudoku_with_box_shape(box_shape, &mut rng);
+        let mut sudoku = Grid::empty(box_shape);
+        for (row, column, value) in solution.fields().step_by(3) {
+            sudoku.set(row, column, value);
+        }
+        let start = Instant::now();
+        assert_eq!(count_solutions(&sudoku, 2), 2, "{:?}", box_shape);
+        assert!(get_solution(&sudoku).unwrap().is_solved(), "{:?}", box_shape);
+        assert!(start.elapsed() < Duration::from_secs(20), "{:?} took {:?}", box_shape, start.elapsed());
+    }
+}
@@ -0,0 +1,72 @@
+/*
+    Transforms : Combining a transform with its inverse, or rotations that add up to a full turn,
+    does nothing, also when the transform turns the shape of the subgrids around, and a transformed
+    puzzle still belongs to its transformed solution
+ */
+
+use sudoku_creator::{
+    generate_graded_sudoku, get_solution, has_unique_solution, parse_sudoku, seeded_rng, BoxShape, RemovalMode, Transform,
+};
+
+
+/// A 6x6 sudoku to solve with subgrids of 2 rows and 3 columns, with a unique solution
+const PUZZLE_6X6: &str = ".6.5.441....1.2.6...413....34...1...";
+
+#[test]
+fn transform_then_inverse_is_identity() {
+    let box_shape = BoxShape::new(2, 3);
+    let sudoku = parse_sudoku(PUZZLE_6X6, Some(box_shape)).unwrap();
+    let swap_bands = Transform::permute_bands(BoxShape::new(3, 2), &[1, 0]).unwrap();
+    let relabel = Transform::relabel(box_shape, &[4, 6, 1, 2, 5, 3]).unwrap();
+    let transforms = [
+        Transform::transpose(box_shape),
+        relabel.then(&Transform::transpose(box_shape)).then(&swap_bands),
+        Transform::random(box_shape, &mut seeded_rng(5)),
+    ];
+
+    for transform in transforms {
+        let inverse = transform.inverse();
+        assert_eq!(inverse.box_shape(), transform.output_box_shape());
+        assert_eq!(inverse.output_box_shape(), box_shape);
+        assert_eq!(transform.then(&inverse), Transform::identity(box_shape));
+        assert_eq!(inverse.then(&transform), Transform::identity(transform.output_box_shape()));
+        assert_eq!(inverse.apply(&transform.apply(&sudoku)), sudoku);
+    }
+    assert_eq!(Transform::transpose(box_shape).output_box_shape(), BoxShape::new(3, 2));
+}
+
+#[test]
+fn rotations_of_a_full_turn_are_identity() {
+    for box_shape in [BoxShape::new(3, 3), BoxShape::new(2, 3)] {
+        let three_quarters = Transform::rotate(box_shape, 3);
+        let quarter = Transform::rotate(three_quarters.output_box_shape(), 1);
+        assert_eq!(three_quarters.then(&quarter), Transform::identity(box_shape), "{:?}", box_shape);
+
+        let half = Transform::rotate(box_shape, 2);
+        assert_eq!(half.then(&half), Transform::identity(box_shape), "{:?}", box_shape);
+        assert_eq!(Transform::rotate(box_shape, 4), Transform::identity(box_shape), "{:?}", box_shape);
+    }
+}
+
+#[test]
+fn transformed_puzzle_keeps_its_solution() {
+    for (box_shape, num_to_delete) in [(BoxShape::new(3, 3), 50), (BoxShape::new(2, 3), 20)] {
+        let puzzle = generate_graded_sudoku(box_shape, num_to_delete, RemovalMode::Unique, 8).unwrap();
+        let mut rng = seeded_rng(8);
+        for _ in 0..5 {
+            let transform = Transform::random(box_shape, &mut rng);
+            let transformed = transform.apply_to_puzzle(&puzzle);
+
+            assert_eq!(transformed.seed, puzzle.seed);
+            assert_eq!(transformed.sudoku.box_shape(), transform.output_box_shape());
+            assert_eq!(transformed.solution.box_shape(), transform.output_box_shape());
+            assert_eq!(transformed.sudoku.empty_fields().len(), puzzle.sudoku.empty_fields().len());
+            // every given number is still the number of the solution in the same field
+            for (row, column, value) in transformed.sudoku.fields().filter(|(_, _, value)| value.is_some()) {
+                assert_eq!(transformed.solution.get(row, column), value, "{:?}", box_shape);
+            }
+            assert!(has_unique_solution(&transformed.sudoku), "{:?}", box_shape);
+            assert_eq!(get_solution(&transformed.sudoku), Ok(transformed.solution.clone()), "{:?}", box_shape);
+        }
+    }
+}